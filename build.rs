@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// turns the word lists in words/ into tables that get compiled into the game (see src/words.rs),
+// so nothing has to be read or allocated when it starts. The lists get checked on the way:
+// every secret has to be a valid guess (or it could never be won), and no secret can be in there twice
+
+const GUESSES: &str = "words/guesses.txt";
+const SECRETS: &str = "words/secrets.txt";
+const PACK: &str = "src/words/pack.rs";
+
+fn main() {
+    println!("cargo:rerun-if-changed={GUESSES}");
+    println!("cargo:rerun-if-changed={SECRETS}");
+    println!("cargo:rerun-if-changed={PACK}");
+
+    let mut guesses = read(GUESSES);
+    guesses.sort();
+    guesses.dedup(); // a word listed twice is harmless, just left out the second time
+    let secrets = read(SECRETS); // order kept, the daily puzzle picks by index so a given day's word doesn't change
+
+    let valid: HashSet<u32> = guesses.iter().copied().collect();
+    let mut seen = HashSet::new();
+    for secret in &secrets {
+        if !valid.contains(secret) {
+            panic!("{SECRETS}: \"{}\" isn't in {GUESSES}, so it could never be guessed", unpack(*secret).to_lowercase());
+        }
+        if !seen.insert(*secret) {
+            panic!("{SECRETS}: \"{}\" is in there twice", unpack(*secret).to_lowercase());
+        }
+    }
+
+    // the guesses go in a hash table (open addressing, at most half full) so looking one up is a hash and a probe or two
+    let bits = (guesses.len() * 2).next_power_of_two().trailing_zeros();
+    let mut table = vec![0u32; 1 << bits]; // 0 is an empty slot, no word packs to 0
+    for word in &guesses {
+        let mut slot = hash(*word, bits);
+        while table[slot] != 0 {
+            slot = (slot + 1) % table.len();
+        }
+        table[slot] = *word;
+    }
+
+    let mut out = String::from("// generated by build.rs from words/guesses.txt and words/secrets.txt\n\n");
+    out.push_str(&format!("const GUESS_BITS: u32 = {bits};\n"));
+    out.push_str(&format!("static GUESS_TABLE: [u32; {}] = {};\n", table.len(), array(&table)));
+    out.push_str(&format!("static SECRETS: [u32; {}] = {};\n", secrets.len(), array(&secrets)));
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("words.rs");
+    fs::write(path, out).unwrap();
+}
+
+fn read(path: &str) -> Vec<u32> {
+    let text = fs::read_to_string(path).unwrap_or_else(|e| panic!("Could not read {path}: {e}"));
+    text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).map(|(number, word)| {
+        pack(word.trim()).unwrap_or_else(|| panic!("{path}: line {}, \"{word}\" isn't five letters a to z", number + 1))
+    }).collect()
+}
+
+include!("src/words/pack.rs"); // pack, hash and unpack
+
+fn array(values: &[u32]) -> String {
+    let lines: Vec<String> = values.chunks(16).map(|chunk| {
+        chunk.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(", ")
+    }).collect();
+    format!("[\n    {},\n]", lines.join(",\n    "))
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use crate::layout::WORD_LENGTH;
+use crate::tiles::Face;
+use crate::Letter;
+
+// the board's animations, each a number of frames the Board draws one after another (see Board::animate)
+// these just say what frame n looks like. A key press skips whichever one is playing straight to its last frame
+// (and cuts short the pauses on the game over screens), and Motion can slow them down, speed them up or turn them off
+
+pub const FRAME: Duration = Duration::from_millis(35); // how long each frame stays up
+
+const FLIP_FRAMES: usize = 4; // per tile: half height, side on, half height coloured, coloured
+const BOUNCE_FRAMES: usize = 2; // per tile: how long it's up for
+const BOUNCE_GAP: usize = 1; // frames between one tile going up and the next
+const SHAKE: [i16; 8] = [-2, 2, -2, 2, -1, 1, -1, 0]; // columns left or right of where the row belongs
+
+// how much the board moves, a preference for each profile. --reduced-motion and --speed change it for one game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    pub animate: bool, // false for reduced motion: everything just appears in its final state
+    pub speed: f64, // 2 is twice as fast. Scales the pauses too (they're there whether things move or not)
+}
+
+impl Default for Motion {
+    fn default() -> Motion {
+        Motion { animate: true, speed: 1.0 }
+    }
+}
+
+impl Motion {
+    const FAST: f64 = 2.0;
+
+    pub fn scale(&self, duration: Duration) -> Duration {
+        duration.div_f64(self.speed)
+    }
+
+    pub fn parse_speed(text: &str) -> Option<f64> { // a multiplier, e.g. "1.5" (anything too big is as good as instant anyway)
+        text.trim().trim_end_matches('x').parse().ok().filter(|speed: &f64| (0.1..=100.0).contains(speed))
+    }
+
+    pub fn name(&self) -> String {
+        match (self.animate, self.speed) {
+            (false, _) => String::from("off"),
+            (true, 1.0) => String::from("normal"),
+            (true, Motion::FAST) => String::from("fast"),
+            (true, speed) => format!("{speed}x"),
+        }
+    }
+
+    pub fn next(&self) -> Motion { // for cycling through in the settings menu: normal, fast, off
+        match (self.animate, self.speed) {
+            (true, 1.0) => Motion { animate: true, speed: Motion::FAST },
+            (true, Motion::FAST) => Motion { animate: false, speed: 1.0 },
+            _ => Motion::default(), // including any other speed given on the command line
+        }
+    }
+}
+
+// flip: a scored guess's tiles turn over one by one to show their colours
+pub fn flip_frames() -> usize {
+    WORD_LENGTH * FLIP_FRAMES
+}
+
+pub(crate) fn flip(colours: &[Letter; 5], frame: usize) -> [Face; 5] {
+    let mut faces = [Face::Plain; 5];
+    for (index, face) in faces.iter_mut().enumerate() {
+        *face = match frame.checked_sub(index * FLIP_FRAMES) {
+            None => Face::Plain, // not its turn yet
+            Some(0) => Face::Half(None),
+            Some(1) => Face::Edge,
+            Some(2) => Face::Half(Some(colours[index])),
+            Some(_) => Face::Scored(colours[index]),
+        };
+    }
+    faces
+}
+
+// shake: a guess that wasn't allowed wobbles side to side
+pub fn shake_frames() -> usize {
+    SHAKE.len()
+}
+
+pub fn shake(frame: usize) -> i16 {
+    SHAKE[frame.min(SHAKE.len() - 1)]
+}
+
+// bounce: the tiles of a winning guess jump up a row one after another, in a wave
+pub fn bounce_frames() -> usize {
+    (WORD_LENGTH - 1) * BOUNCE_GAP + BOUNCE_FRAMES + 1 // (the last frame has them all back down)
+}
+
+pub fn bounce(frame: usize, tile: usize) -> bool { // whether a tile's up in the air
+    frame.checked_sub(tile * BOUNCE_GAP).is_some_and(|f| f < BOUNCE_FRAMES)
+}
@@ -0,0 +1,174 @@
+use std::io::{self, Write};
+
+use termion::raw::IntoRawMode;
+
+use crate::input::{stdin_keys, Key};
+use crate::Word;
+
+// custom challenges: one player picks the secret word and gets a code to pass on, the other plays it with the code
+// the code isn't encryption, just enough scrambling that the word can't be read off it at a glance
+// it's the word as a base-26 number, mixed up, written as five Crockford base32 digits, then two check digits, e.g. "7KQ4-M2D"
+// the check is the first five digits' value mod a prime, so changing any one character (or swapping two neighbours in
+// the first five) always makes it wrong, and a mistyped code gets caught instead of turning into some other word
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ"; // Crockford base32: no I, L, O or U to misread
+const BITS: u32 = 24; // 26^5 fits in 24 bits
+const MASK: u32 = 0x5A_C3E9;
+const MULTIPLIER: u32 = 0x9E37_79B1; // odd, so multiplying by it can be undone
+const CHECK: u32 = 1021; // prime, and under 32^2 so the check fits in two digits
+
+pub fn encode(word: &Word) -> String {
+    let mut value: u32 = 0;
+    for letter in word.contents().bytes() {
+        value = value * 26 + (letter - b'A') as u32;
+    }
+    let scrambled = rotate(((value ^ MASK).wrapping_mul(MULTIPLIER)) & low_bits(), 7);
+    let digits = (scrambled as u64) << 10 | (scrambled % CHECK) as u64; // 5 digits of word, 2 of check
+
+    let mut code = String::new();
+    for index in (0..7).rev() {
+        code.push(ALPHABET[(digits >> (index * 5) & 31) as usize] as char);
+    }
+    code.insert(4, '-');
+    code
+}
+
+pub fn decode(code: &str) -> Result<String, &'static str> {
+    // returns the word a code was made from. Still needs checking against the word list like any other word
+    let mut digits: u64 = 0;
+    let mut count = 0;
+    for c in code.trim().chars().filter(|c| *c != '-' && *c != ' ') {
+        let c = match c.to_ascii_uppercase() {
+            'I' | 'L' => '1', // the usual Crockford forgiveness for misread characters
+            'O' => '0',
+            c => c,
+        };
+        let digit = ALPHABET.iter().position(|a| *a as char == c).ok_or("Challenge codes only use letters and numbers")?;
+        digits = digits << 5 | digit as u64;
+        count += 1;
+    }
+    if count != 7 {
+        return Err("That isn't a challenge code");
+    }
+
+    let (scrambled, check) = ((digits >> 10) as u32, (digits & 1023) as u32);
+    if scrambled % CHECK != check || scrambled > low_bits() {
+        return Err("That challenge code has a typo in it");
+    }
+    let value = (rotate(scrambled, BITS - 7).wrapping_mul(inverse(MULTIPLIER)) & low_bits()) ^ MASK;
+    if value >= 26u32.pow(5) {
+        return Err("That challenge code has a typo in it");
+    }
+
+    let mut word = [b'A'; 5];
+    let mut rest = value;
+    for letter in word.iter_mut().rev() {
+        *letter = b'A' + (rest % 26) as u8;
+        rest /= 26;
+    }
+    Ok(String::from_utf8_lossy(&word).to_string())
+}
+
+fn low_bits() -> u32 {
+    (1 << BITS) - 1
+}
+
+fn rotate(value: u32, by: u32) -> u32 { // left, within the low 24 bits
+    (value << by | value >> (BITS - by)) & low_bits()
+}
+
+fn inverse(odd: u32) -> u32 {
+    // multiplicative inverse mod 2^32 by Newton's method (each step doubles the correct bits). Works mod 2^24 too
+    let mut inverse = odd;
+    for _ in 0..5 {
+        inverse = inverse.wrapping_mul(2u32.wrapping_sub(odd.wrapping_mul(inverse)));
+    }
+    inverse
+}
+
+pub fn read_hidden(prompt: &str) -> io::Result<Option<String>> {
+    // reads a word without showing it (letters come up as *), so whoever's receiving the challenge can't peek
+    // returns None if Esc or Ctrl-C is pressed
+    let mut screen = io::stdout().into_raw_mode()?;
+    write!(screen, "{prompt}")?;
+    screen.flush()?;
+
+    let mut word = String::new();
+    for key in stdin_keys() {
+        match key? {
+            Key::Char('\n') => break,
+            Key::Char(c) if c.is_alphabetic() && word.chars().count() < 5 => {
+                word.push(c);
+                write!(screen, "*")?;
+            },
+            Key::Backspace if !word.is_empty() => {
+                word.pop();
+                write!(screen, "\u{8} \u{8}")?; // back, blank, back
+            },
+            Key::Esc | Key::Ctrl('c') => {
+                write!(screen, "\r\n")?;
+                return Ok(None);
+            },
+            _ => (),
+        }
+        screen.flush()?;
+    }
+    write!(screen, "\r\n")?;
+    Ok(Some(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::words::{Guesses, Secrets};
+
+    fn codes() -> impl Iterator<Item = (String, String)> {
+        let secrets = Secrets::Builtin;
+        (0..secrets.len()).map(move |i| {
+            let word = Word::try_new(secrets.get(i), &Guesses::Builtin).unwrap();
+            (word.contents().to_string(), encode(&word))
+        })
+    }
+
+    #[test]
+    fn round_trip() {
+        for (word, code) in codes() {
+            assert_eq!(decode(&code), Ok(word.clone()), "{code}");
+            // read back the way people type them
+            let typed = code.replace('-', " ").replace('1', "l").replace('0', "O").to_lowercase();
+            assert_eq!(decode(&typed), Ok(word), "{typed}");
+        }
+    }
+
+    #[test]
+    fn typos_are_rejected() {
+        for (word, code) in codes() {
+            let code: Vec<char> = code.chars().collect();
+            let positions: Vec<usize> = (0..code.len()).filter(|i| code[*i] != '-').collect();
+            for position in &positions {
+                for digit in ALPHABET.iter().map(|d| *d as char).filter(|d| *d != code[*position]) {
+                    let mut typo = code.clone();
+                    typo[*position] = digit;
+                    let typo: String = typo.into_iter().collect();
+                    assert!(decode(&typo).is_err(), "{typo} was taken for {:?}, made from {word}", decode(&typo));
+                }
+            }
+            for pair in positions[..5].windows(2) {
+                let mut swapped = code.clone();
+                swapped.swap(pair[0], pair[1]);
+                if swapped != code {
+                    let swapped: String = swapped.into_iter().collect();
+                    assert!(decode(&swapped).is_err(), "{swapped} was taken for {:?}, made from {word}", decode(&swapped));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn not_codes() {
+        assert_eq!(decode("0AP0-1V"), Err("That isn't a challenge code"));
+        assert_eq!(decode("0AP0-1VJJ"), Err("That isn't a challenge code"));
+        assert_eq!(decode("0AP0-1V!"), Err("Challenge codes only use letters and numbers"));
+        assert_eq!(decode("ZZZZ-ZZZ"), Err("That challenge code has a typo in it"));
+    }
+}
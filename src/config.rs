@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::animation::Motion;
+use crate::daily::Timezone;
+use crate::input::{Command, KeyBindings};
+use crate::profile::Prefs;
+use crate::stats;
+use crate::theme::{ColourSupport, Rgb, Theme, Themes};
+use crate::tiles::BoardStyle;
+use crate::KeyboardLayout;
+
+// user settings, read from ~/.config/wordle/config.toml (or $XDG_CONFIG_HOME/wordle/config.toml)
+// only a small subset of TOML is understood: [sections], key = value, and # comments
+// a missing file just means defaults
+//
+// the settings a profile can change for itself are set here too, as the defaults for profiles that haven't
+// (and the settings menu can save them back here, see save_defaults):
+//   hard = false
+//   theme = "dark"
+//   layout = "qwerty"
+//   style = "ascii"
+//   animations = true
+//   speed = 1
+// along with some that are only set here:
+//   stats_dir = "~/Dropbox/wordle"    # where stats, history, profiles and saved games are kept
+//   [words]
+//   guesses = "~/words/guesses.txt"   # word lists to use instead of the built-in ones, a word per line
+//   secrets = "~/words/secrets.txt"   # (secret words have to be in the guess list too)
+
+const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub timezone: Timezone, // when the daily puzzle rolls over
+    pub themes: Themes, // the default theme and any extra ones (see theme.rs)
+    pub colours: Option<ColourSupport>, // None works it out from the terminal
+    pub keys: KeyBindings, // see input.rs
+    pub defaults: Prefs, // for profiles that haven't set their own (except the theme, that's themes.default)
+    pub stats_dir: Option<PathBuf>, // None for the usual data directory (see stats::data_dir)
+    pub guess_list: Option<PathBuf>, // None for the built-in word lists
+    pub secret_list: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            timezone: Timezone::Local,
+            themes: Themes::default(),
+            colours: None,
+            keys: KeyBindings::default(),
+            defaults: Prefs::default(),
+            stats_dir: None,
+            guess_list: None,
+            secret_list: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Config, String> {
+        let path = config_path();
+        match fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("Could not read {}: {e}", path.display())),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Config, String> {
+        let values = parse_values(text)?;
+        let mut config = Config::default();
+        for (key, value) in &values {
+            match key.as_str() {
+                "timezone" => config.timezone = Timezone::parse(value).ok_or(format!("invalid timezone \"{value}\" (expected local, UTC or an offset like +05:30)"))?,
+                "theme" => config.themes.default = value.to_string(),
+                "colours" | "colors" => config.colours = ColourSupport::parse(value).ok_or(format!("invalid colours \"{value}\" (expected auto, truecolor, 256, 16 or none)"))?,
+                "hard" => config.defaults.hard = parse_bool(key, value)?,
+                "layout" => config.defaults.layout = KeyboardLayout::parse(value).ok_or(format!("invalid layout \"{value}\" (expected qwerty, azerty, qwertz, dvorak or colemak)"))?,
+                "style" => config.defaults.style = BoardStyle::parse(value).ok_or(format!("invalid style \"{value}\" (expected ascii or tiles)"))?,
+                "animations" => config.defaults.motion.animate = parse_bool(key, value)?,
+                "speed" => config.defaults.motion.speed = Motion::parse_speed(value).ok_or(format!("invalid speed \"{value}\" (expected a number like 2 or 0.5)"))?,
+                "stats_dir" => config.stats_dir = Some(expand_home(value)),
+                "words.guesses" => config.guess_list = Some(expand_home(value)),
+                "words.secrets" => config.secret_list = Some(expand_home(value)),
+                key if key.starts_with("keys.") => { // [keys] section: command = "key, key"
+                    let command = Command::parse(&key["keys.".len()..]).ok_or(format!("unknown setting \"{key}\" (keys can be set for quit, settings and help)"))?;
+                    config.keys.set(command, value)?;
+                },
+                key if key.starts_with("themes.") => { // [themes.NAME] sections: correct, present and absent colours
+                    let Some((name, role)) = key["themes.".len()..].rsplit_once('.') else {
+                        return Err(format!("unknown setting \"{key}\""));
+                    };
+                    let colour = Rgb::parse(value).ok_or(format!("invalid colour \"{value}\" for {key} (expected something like \"#6aaa64\")"))?;
+                    let theme = match config.themes.custom.iter_mut().find(|t| t.name == name) {
+                        Some(theme) => theme,
+                        None => {
+                            let mut theme = Theme::builtin().remove(0); // anything left out comes from the dark theme
+                            theme.name = name.to_string();
+                            config.themes.custom.push(theme);
+                            config.themes.custom.last_mut().unwrap() // just pushed
+                        },
+                    };
+                    match role {
+                        "correct" => theme.correct = colour,
+                        "present" => theme.present = colour,
+                        "absent" => theme.absent = colour,
+                        _ => return Err(format!("unknown setting \"{key}\" (themes have correct, present and absent)")),
+                    }
+                },
+                _ => return Err(format!("unknown setting \"{key}\"")),
+            }
+        }
+        if config.themes.get(&config.themes.default).is_none() {
+            return Err(format!("unknown theme \"{}\"", config.themes.default));
+        }
+        config.keys.check()?;
+        config.themes.custom.sort_by(|a, b| a.name.cmp(&b.name)); // the file's order is lost in parsing, so keep it predictable
+        Ok(config)
+    }
+}
+
+pub fn save_defaults(prefs: &Prefs) -> io::Result<()> {
+    // writes the settings into the config file as everyone's defaults, leaving everything else in it
+    // (comments, themes, keys...) as it was. Ones already there are changed in place, new ones go after the last of them
+    let path = config_path();
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    let top_level = lines.iter().position(|line| strip_comment(line).trim().starts_with('[')).unwrap_or(lines.len()); // lines before any [section]
+    let mut new = Vec::new();
+    for (key, value) in prefs.values() {
+        let existing = lines[..top_level].iter().position(|line| strip_comment(line).split_once('=').is_some_and(|(k, _)| k.trim() == key));
+        match existing {
+            Some(index) => {
+                let comment = lines[index][strip_comment(&lines[index]).len()..].to_string(); // kept, if there was one
+                lines[index] = format!("{key} = {value}{}", if comment.is_empty() { String::new() } else { format!(" {}", comment.trim()) });
+            },
+            None => new.push(format!("{key} = {value}")),
+        }
+    }
+    let after = lines[..top_level].iter().rposition(|line| !strip_comment(line).trim().is_empty()).map_or(0, |index| index + 1); // (before any [section])
+    lines.splice(after..after, new);
+    fs::create_dir_all(config_dir())?;
+    stats::write_atomic(&path, &(lines.join("\n") + "\n"))
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("invalid {key} \"{value}\" (expected true or false)")),
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf { // ~/ at the start is the home directory
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+pub fn config_dir() -> PathBuf {
+    // $XDG_CONFIG_HOME/wordle, falling back to ~/.config/wordle, then the working directory
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir).join("wordle");
+    }
+    match std::env::var_os("HOME").filter(|h| !h.is_empty()) {
+        Some(home) => PathBuf::from(home).join(".config/wordle"),
+        None => PathBuf::from("."),
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    config_dir().join(CONFIG_FILE)
+}
+
+pub(crate) fn parse_values(text: &str) -> Result<HashMap<String, String>, String> {
+    // flattens the file into "section.key" -> value, with quotes stripped off strings
+    let mut values: HashMap<String, String> = HashMap::new();
+    let mut section = String::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected key = value", number + 1));
+        };
+        let key = match section.as_str() {
+            "" => key.trim().to_string(),
+            section => format!("{section}.{}", key.trim()),
+        };
+        let value = value.trim();
+        let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+        values.insert(key, value.to_string());
+    }
+    Ok(values)
+}
+
+fn strip_comment(line: &str) -> &str {
+    // a # starts a comment unless it's inside quotes (colours like "#6aaa64" need to survive)
+    let mut in_quotes = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return &line[..index],
+            _ => (),
+        }
+    }
+    line
+}
@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Offset, Utc};
+
+// calendar days for the daily puzzle
+// a "day" is a day number (days since 1970-01-01) in the player's timezone, so the puzzle and streaks roll over at their midnight
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timezone {
+    Local, // whatever the system says, including daylight saving changes
+    Fixed(FixedOffset), // "UTC" or an offset like "+05:30", for people whose system clock is set to something else
+}
+
+impl Timezone {
+    pub fn parse(text: &str) -> Option<Timezone> {
+        let text = text.trim();
+        match text.to_lowercase().as_str() {
+            "local" => return Some(Timezone::Local),
+            "utc" | "gmt" | "z" => return Some(Timezone::Fixed(Utc.fix())),
+            _ => (),
+        }
+
+        // "+05:30", "-8", "UTC+2", "+0530"
+        let offset = text.strip_prefix("UTC").or(text.strip_prefix("utc")).unwrap_or(text);
+        let (sign, rest) = match offset.chars().next()? {
+            '+' => (1, &offset[1..]),
+            '-' => (-1, &offset[1..]),
+            _ => return None,
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if rest.len() == 4 && rest.is_ascii() => rest.split_at(2), // (split_at would panic inside a multi-byte character)
+            None => (rest, "0"),
+        };
+        let hours: i32 = hours.parse().ok()?;
+        let minutes: i32 = minutes.parse().ok()?;
+        if hours > 14 || minutes > 59 {
+            return None;
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).map(Timezone::Fixed)
+    }
+
+    fn now(&self) -> DateTime<FixedOffset> {
+        match self {
+            Timezone::Local => Local::now().fixed_offset(),
+            Timezone::Fixed(offset) => Utc::now().with_timezone(offset),
+        }
+    }
+}
+
+pub fn today(timezone: &Timezone) -> i64 {
+    day_number(timezone.now().date_naive())
+}
+
+fn day_number(date: NaiveDate) -> i64 {
+    (date - NaiveDate::default()).num_days() // NaiveDate::default() is 1970-01-01
+}
+
+pub fn until_tomorrow(timezone: &Timezone) -> Duration {
+    // time left until midnight in the player's timezone, when the next daily puzzle unlocks
+    let now = timezone.now();
+    let midnight = now.date_naive().succ_opt().and_then(|d| d.and_hms_opt(0, 0, 0));
+    match midnight {
+        Some(midnight) => (midnight - now.naive_local()).to_std().unwrap_or_default(),
+        None => Duration::ZERO, // end of the calendar, not going to happen
+    }
+}
+
+pub fn format_countdown(left: Duration) -> String {
+    let seconds = left.as_secs();
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+pub fn puzzle_index(day: i64, puzzle_count: usize) -> usize {
+    // which secret word belongs to a day. Scrambled (splitmix64) so the answers don't just walk through the alphabet
+    let mut z = (day as u64).wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^= z >> 31;
+    (z % puzzle_count as u64) as usize
+}
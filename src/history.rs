@@ -0,0 +1,191 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+
+use crate::profile::Profile;
+
+// game history: one line per finished game, appended to wordle_history.txt next to the stats file
+// the stats file only has running totals, this is what exports (and anything wanting per-game detail) read from
+// lines look like: timestamp <tab> mode key <tab> secret <tab> won (1/0) <tab> guesses separated by commas
+
+const HISTORY_FILE: &str = "wordle_history.txt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    pub timestamp: u64, // unix seconds when the game finished
+    pub key: String, // which stats the game counted towards, e.g. "normal-hard"
+    pub secret: String,
+    pub guesses: Vec<String>,
+    pub win: bool,
+}
+
+impl GameRecord {
+    pub fn new(key: String, secret: String, guesses: Vec<String>, win: bool) -> GameRecord {
+        GameRecord { timestamp: now_secs(), key, secret, guesses, win }
+    }
+
+    pub fn turns(&self) -> usize {
+        self.guesses.len()
+    }
+
+    fn to_line(&self) -> String {
+        format!("{}\t{}\t{}\t{}\t{}", self.timestamp, self.key, self.secret, self.win as u8, self.guesses.join(","))
+    }
+
+    fn from_line(line: &str) -> Option<GameRecord> {
+        let mut fields = line.split('\t');
+        let timestamp = fields.next()?.parse().ok()?;
+        let key = fields.next()?.to_string();
+        let secret = fields.next()?.to_string();
+        let win = match fields.next()? {
+            "1" => true,
+            "0" => false,
+            _ => return None,
+        };
+        let guesses = match fields.next()? {
+            "" => Vec::new(),
+            list => list.split(',').map(String::from).collect(),
+        };
+        if fields.next().is_some() || key.is_empty() {
+            return None;
+        }
+        Some(GameRecord { timestamp, key, secret, guesses, win })
+    }
+}
+
+pub fn history_path(profile: &Profile) -> PathBuf {
+    profile.dir().join(HISTORY_FILE)
+}
+
+pub(crate) fn append(profile: &Profile, record: &GameRecord) -> io::Result<()> {
+    // only called by stats::record, which holds the stats lock, so appends from two games can't interleave
+    let mut file = OpenOptions::new().create(true).append(true).open(history_path(profile))?;
+    file.write_all(format!("{}\n", record.to_line()).as_bytes())?;
+    file.sync_all()
+}
+
+pub fn load(profile: &Profile) -> io::Result<Vec<GameRecord>> {
+    // a game that crashed mid-append can leave a broken last line, so lines that don't parse are skipped rather than failing everything
+    match fs::read_to_string(history_path(profile)) {
+        Ok(text) => Ok(text.lines().filter_map(GameRecord::from_line).collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()), // no games finished yet
+        Err(e) => Err(e),
+    }
+}
+
+// things the stats screen shows that running totals can't answer
+pub struct Insights {
+    pub openers: Vec<Opener>, // most used first guesses, most used first
+    pub hardest: Vec<String>, // secrets that beat you, most recent first
+    pub trend: Vec<f64>, // win rate (0 to 1) over time, oldest first
+}
+
+pub struct Opener {
+    pub word: String,
+    pub played: usize,
+    pub won: usize,
+}
+
+pub fn insights(records: &[GameRecord], key: &str, trend_points: usize) -> Insights {
+    let games: Vec<&GameRecord> = records.iter().filter(|r| r.key == key).collect();
+
+    // tally first guesses
+    let mut openers: Vec<Opener> = Vec::new();
+    for game in &games {
+        let Some(first) = game.guesses.first() else { continue };
+        match openers.iter_mut().find(|o| o.word == *first) {
+            Some(opener) => {
+                opener.played += 1;
+                opener.won += game.win as usize;
+            },
+            None => openers.push(Opener { word: first.clone(), played: 1, won: game.win as usize }),
+        }
+    }
+    openers.sort_by(|a, b| b.played.cmp(&a.played).then(b.won.cmp(&a.won)).then(a.word.cmp(&b.word)));
+    openers.truncate(3);
+
+    let mut hardest: Vec<String> = Vec::new();
+    for game in games.iter().rev().filter(|g| !g.win) {
+        if !hardest.contains(&game.secret) {
+            hardest.push(game.secret.clone());
+        }
+    }
+    hardest.truncate(5);
+
+    // split the games into (up to) trend_points equal-ish buckets and take the win rate of each
+    let points = trend_points.min(games.len());
+    let mut trend: Vec<f64> = Vec::new();
+    for point in 0..points {
+        let bucket = &games[point * games.len() / points..(point + 1) * games.len() / points];
+        trend.push(bucket.iter().filter(|g| g.win).count() as f64 / bucket.len() as f64);
+    }
+
+    Insights { openers, hardest, trend }
+}
+
+pub fn sparkline(trend: &[f64]) -> String {
+    const TICKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    trend.iter().map(|rate| TICKS[(rate.clamp(0.0, 1.0) * 7.0).round() as usize]).collect()
+}
+
+pub fn to_csv(records: &[GameRecord]) -> String {
+    let mut out = String::from("date,mode,secret,won,turns,guesses\n");
+    for record in records {
+        // nothing here can contain a comma or quote except the guess list, which gets quoted
+        out.push_str(&format!("{},{},{},{},{},\"{}\"\n",
+            format_timestamp(record.timestamp),
+            record.key,
+            record.secret,
+            record.win,
+            record.turns(),
+            record.guesses.join(" "),
+        ));
+    }
+    out
+}
+
+pub fn to_json(records: &[GameRecord]) -> String {
+    let mut out = String::from("[\n");
+    for (index, record) in records.iter().enumerate() {
+        let guesses: Vec<String> = record.guesses.iter().map(|g| json_string(g)).collect();
+        out.push_str(&format!("  {{\"date\": {}, \"timestamp\": {}, \"mode\": {}, \"secret\": {}, \"won\": {}, \"turns\": {}, \"guesses\": [{}]}}",
+            json_string(&format_timestamp(record.timestamp)),
+            record.timestamp,
+            json_string(&record.key),
+            json_string(&record.secret),
+            record.win,
+            record.turns(),
+            guesses.join(", "),
+        ));
+        out.push_str(if index + 1 < records.len() { ",\n" } else { "\n" });
+    }
+    out.push_str("]\n");
+    out
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub fn format_timestamp(timestamp: u64) -> String { // unix seconds -> "2024-01-21T15:04:05Z" (UTC)
+    match DateTime::<Utc>::from_timestamp(timestamp as i64, 0) {
+        Some(time) => time.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        None => String::from("?"), // far beyond anything a clock gives
+    }
+}
+
+pub fn now_secs() -> u64 { // unix seconds, what games and backups are stamped with
+    Utc::now().timestamp().max(0) as u64
+}
@@ -0,0 +1,254 @@
+use std::collections::VecDeque;
+use std::io::{self, stdin};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use termion::input::TermRead;
+
+// where the Board's key presses come from: the keyboard (read on its own thread, see read_keys),
+// a recording being played back (see recording.rs), or just a list of keys
+//
+// and which keys do what, besides typing and editing a guess. Those can be changed in the config file:
+//   [keys]
+//   quit = "`"
+//   settings = "esc, f2"    # more than one key can do the same thing
+//   help = "?"
+
+// a key press, as far as the game's concerned. The terminal's own keys only get as far as stdin_keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char), // Enter comes through as '\n'
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Esc,
+    F(u8),
+    Ctrl(char),
+    Alt(char),
+}
+
+pub fn stdin_keys() -> impl Iterator<Item = io::Result<Key>> {
+    // key presses from the terminal (which has to be in raw mode). Ones the game has no use for, like Page Up, are skipped
+    stdin().keys().filter_map(|key| {
+        use termion::event::Key as Term;
+        let key = match key {
+            Ok(Term::Char(c)) => Key::Char(c),
+            Ok(Term::Backspace) => Key::Backspace,
+            Ok(Term::Delete) => Key::Delete,
+            Ok(Term::Left) => Key::Left,
+            Ok(Term::Right) => Key::Right,
+            Ok(Term::Up) => Key::Up,
+            Ok(Term::Down) => Key::Down,
+            Ok(Term::Home) => Key::Home,
+            Ok(Term::End) => Key::End,
+            Ok(Term::Esc) => Key::Esc,
+            Ok(Term::F(n)) => Key::F(n),
+            Ok(Term::Ctrl(c)) => Key::Ctrl(c),
+            Ok(Term::Alt(c)) => Key::Alt(c),
+            Ok(_) => return None,
+            Err(error) => return Some(Err(error)),
+        };
+        Some(Ok(key))
+    })
+}
+
+pub trait InputSource {
+    // the next key, or Timeout if none came in time so the board can update anything live.
+    // Disconnected means no more keys are ever coming
+    fn next_key(&mut self, timeout: Duration) -> Result<Key, RecvTimeoutError>;
+}
+
+impl InputSource for Receiver<Key> {
+    fn next_key(&mut self, timeout: Duration) -> Result<Key, RecvTimeoutError> {
+        self.recv_timeout(timeout)
+    }
+}
+
+impl InputSource for VecDeque<Key> { // keys decided up front, handed over straight away
+    fn next_key(&mut self, _timeout: Duration) -> Result<Key, RecvTimeoutError> {
+        self.pop_front().ok_or(RecvTimeoutError::Disconnected)
+    }
+}
+
+pub fn read_keys(sender: Sender<Key>, only_quit: Option<(Vec<Key>, Key)>) {
+    // passes key presses from stdin to the board. During a replay only the quit keys get through (Esc counts as one too),
+    // as the quit key the board knows about (the recording's, which can differ from the player's now)
+    thread::spawn(move || {
+        for key in stdin_keys() {
+            let key = match (key, &only_quit) {
+                (Ok(key), None) => key,
+                (Ok(key), Some((quit, send))) if quit.contains(&key) || key == Key::Esc => *send,
+                (Ok(_), Some(_)) => continue,
+                (Err(_), _) => break,
+            };
+            if sender.send(key).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// things a key can be bound to. Everything else (letters, Enter, the editing keys) is fixed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Quit, // saves an unfinished game to carry on next time
+    Settings, // the settings menu, over whatever's on screen
+    Help, // how to play and what the keys are
+}
+
+impl Command {
+    pub const ALL: [Command; 3] = [Command::Quit, Command::Settings, Command::Help];
+
+    pub fn name(&self) -> &'static str { // as it appears in the config file's [keys] section
+        match self {
+            Command::Quit => "quit",
+            Command::Settings => "settings",
+            Command::Help => "help",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Command> {
+        Command::ALL.into_iter().find(|c| c.name() == text.trim().to_lowercase())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    bindings: Vec<(Key, Command)>, // a command can have more than one key
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            bindings: vec![
+                (Key::Char('`'), Command::Quit),
+                (Key::Esc, Command::Settings),
+                (Key::F(2), Command::Settings),
+                (Key::Char('?'), Command::Help),
+            ],
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn command(&self, key: Key) -> Option<Command> {
+        self.bindings.iter().find(|(k, _)| *k == key).map(|(_, command)| *command)
+    }
+
+    pub fn keys(&self, command: Command) -> Vec<Key> {
+        self.bindings.iter().filter(|(_, c)| *c == command).map(|(key, _)| *key).collect()
+    }
+
+    pub fn label(&self, command: Command) -> String { // for the welcome screen and help, e.g. "Esc/F2"
+        self.keys(command).iter().map(label).collect::<Vec<String>>().join("/")
+    }
+
+    pub fn names(&self, command: Command) -> String { // the other way round from set, e.g. "esc, f2"
+        self.keys(command).iter().map(key_name).collect::<Vec<String>>().join(", ")
+    }
+
+    pub fn set(&mut self, command: Command, keys: &str) -> Result<(), String> {
+        // replaces a command's keys with a list from the config file, e.g. "esc, f2"
+        let mut parsed = Vec::new();
+        for name in keys.split(',').map(|k| k.trim().to_lowercase()).filter(|k| !k.is_empty()) {
+            let key = parse_key(&name).ok_or(format!("unknown key \"{name}\" for {}", command.name()))?;
+            if reserved(key) {
+                return Err(format!("\"{name}\" can't be used for {}, it's needed for typing guesses", command.name()));
+            }
+            parsed.push(key);
+        }
+        if parsed.is_empty() {
+            return Err(format!("{} needs at least one key", command.name()));
+        }
+        self.bindings.retain(|(_, c)| *c != command);
+        self.bindings.extend(parsed.into_iter().map(|key| (key, command)));
+        Ok(())
+    }
+
+    pub fn check(&self) -> Result<(), String> { // once they're all set: no key doing two things
+        for (index, (key, command)) in self.bindings.iter().enumerate() {
+            if let Some((_, other)) = self.bindings[..index].iter().find(|(k, c)| k == key && c != command) {
+                return Err(format!("\"{}\" is used for both {} and {}", key_name(key), other.name(), command.name()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn reserved(key: Key) -> bool {
+    // keys that already mean something while typing a guess (or in the settings menu)
+    match key {
+        Key::Char(c) => c.is_alphabetic() || c == '\n' || c == ' ',
+        Key::Backspace | Key::Delete | Key::Left | Key::Right | Key::Up | Key::Down | Key::Home | Key::End | Key::Ctrl('u') => true,
+        _ => false,
+    }
+}
+
+pub fn label(key: &Key) -> String { // a key's name as it's shown on screen: "Esc" rather than "esc", "Ctrl-Q" rather than "ctrl-q"
+    if let Key::Ctrl(c) = key {
+        return format!("Ctrl-{}", c.to_uppercase());
+    }
+    let name = key_name(key);
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(first), Some(_)) => first.to_uppercase().chain(name.chars().skip(1)).collect(),
+        _ => name,
+    }
+}
+
+pub fn key_name(key: &Key) -> String {
+    // what a key's called in recordings and the config file, e.g. "enter", "f2", "ctrl-u"
+    match key {
+        Key::Char('\n') => String::from("enter"),
+        Key::Char(' ') => String::from("space"),
+        Key::Char('\t') => String::from("tab"),
+        Key::Char(c) => c.to_string(),
+        Key::Backspace => String::from("backspace"),
+        Key::Delete => String::from("delete"),
+        Key::Left => String::from("left"),
+        Key::Right => String::from("right"),
+        Key::Up => String::from("up"),
+        Key::Down => String::from("down"),
+        Key::Home => String::from("home"),
+        Key::End => String::from("end"),
+        Key::Esc => String::from("esc"),
+        Key::F(n) => format!("f{n}"),
+        Key::Ctrl(c) => format!("ctrl-{c}"),
+        Key::Alt(c) => format!("alt-{c}"),
+    }
+}
+
+pub fn parse_key(name: &str) -> Option<Key> {
+    let key = match name {
+        "enter" => Key::Char('\n'),
+        "space" => Key::Char(' '),
+        "tab" => Key::Char('\t'),
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "esc" => Key::Esc,
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Key::Char(c),
+                _ => match name.split_once('-') {
+                    Some(("ctrl", c)) => Key::Ctrl(c.chars().next()?),
+                    Some(("alt", c)) => Key::Alt(c.chars().next()?),
+                    _ => Key::F(name.strip_prefix('f')?.parse().ok()?),
+                },
+            }
+        },
+    };
+    Some(key)
+}
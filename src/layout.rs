@@ -0,0 +1,249 @@
+// where everything goes on screen, worked out from the terminal size, word length and number of guesses
+// the Board asks this for positions instead of adding offsets itself, so a different size of board
+// (or a new screen) only needs changing here
+//
+// there are two layouts. The full one has a line between guesses, compact has none
+// and squeezes the keyboard onto one line, for small terminals. The full one can also have large tiles
+// (three rows each, no lines between, see tiles.rs) if the board style wants them and there's room:
+//   full                     compact
+//   W O R D L E              W O R D L E
+//   ---------------------    | C | R | A | N | E |
+//   | C | R | A | N | E |    |   |   |   |   |   |
+//   ---------------------    ...
+//   ...                      message
+//   message                  QWERTYUIOPASDFGHJKLZXCVBNM
+//   Q W E R T Y U I O P      countdown
+//    A S D F G H J K L       press any key
+//      Z X C V B N M
+//   countdown
+//   press any key
+//
+// positions are (column, row), one-based like the terminal's
+
+pub const WORD_LENGTH: usize = 5;
+pub const GUESSES: usize = 6;
+const KEYS: u16 = 26; // letters on the keyboard, the width of compact's one-line keyboard
+const KEYBOARD_WIDTH: u16 = 21; // the full keyboard, a space and a letter per key on its longest row (plus a trailing space)
+const OPPONENT_WIDTH: u16 = 8; // one race opponent's column in the panel beside the board, spacing included
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub col: u16,
+    pub row: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    pub fn centre(&self) -> u16 {
+        self.col + self.width / 2
+    }
+
+    pub fn centred(&self, text: &str) -> u16 { // column to start text at so it sits in the middle
+        self.centre().saturating_sub(text.chars().count() as u16 / 2).max(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub size: (u16, u16), // terminal columns, rows it was worked out for
+    pub compact: bool,
+    pub large: bool, // three-row tiles
+    letters: u16,
+    guesses: u16,
+    board: Rect, // the grid, with the title on its top row
+}
+
+impl Layout {
+    pub fn new(size: (u16, u16), letters: usize, guesses: usize, large_tiles: bool) -> Layout {
+        // large_tiles is whether to use three-row tiles when they fit
+        let (letters, guesses) = (letters as u16, guesses as u16);
+        let compact = !fits(size, Layout::min_size(false, letters, guesses));
+        let large = large_tiles && fits(size, Layout::large_size(letters, guesses));
+        let (width, height) = size;
+        let board_width = match large {
+            true => letters * 6 - 1, // "╭───╮" per letter with a space between
+            false => letters * 4 + 1, // "| C " per letter and the closing "|"
+        };
+        let board_height = match (compact, large) {
+            (true, _) => guesses + 1, // title then the rows
+            (false, true) => guesses * 3 + 2, // title, gap, then the tiles
+            (false, false) => guesses * 2 + 3, // title, gap, then rows with lines between
+        };
+        // the board sits a little above the middle, leaving the space under it for the keyboard
+        let row = match (compact, large) {
+            (true, _) => (height / 2).saturating_sub(guesses / 2 + 2).max(1),
+            (false, true) => (height / 2).saturating_sub(guesses * 3 / 2 + 5).max(1),
+            (false, false) => (height / 2).saturating_sub(guesses + 3), // can change the subtracted amounts as a master "slider" for board position
+        };
+        let col = (width / 2).saturating_sub(board_width / 2); // ensures no overflow if terminal is small
+        Layout { size, compact, large, letters, guesses, board: Rect { col, row, width: board_width, height: board_height } }
+    }
+
+    fn min_size(compact: bool, letters: u16, guesses: u16) -> (u16, u16) {
+        match compact {
+            true => ((letters * 4 + 1).max(KEYS), guesses + 5),
+            false => ((letters * 4 + 1).max(50), guesses * 2 + 10),
+        }
+    }
+
+    fn large_size(letters: u16, guesses: u16) -> (u16, u16) { // the full layout's keyboard and messages under taller tiles
+        ((letters * 6 - 1).max(50), guesses * 3 + 12)
+    }
+
+    pub fn smallest(letters: usize, guesses: usize) -> (u16, u16) { // anything smaller can't be played in
+        Layout::min_size(true, letters as u16, guesses as u16)
+    }
+
+    pub fn fits(size: (u16, u16), letters: usize, guesses: usize) -> bool {
+        fits(size, Layout::smallest(letters, guesses))
+    }
+
+    pub fn board(&self) -> Rect {
+        self.board
+    }
+
+    pub fn title(&self) -> (u16, u16) { // where "W O R D L E" starts
+        (self.board.col + self.board.width / 2 - 5, self.board.row)
+    }
+
+    pub fn guess_row(&self, turn: usize) -> u16 { // turn is 1 to the number of guesses. The row the letters are on
+        match (self.compact, self.large) {
+            (true, _) => self.board.row + turn as u16,
+            (false, true) => self.board.row + turn as u16 * 3,
+            (false, false) => self.board.row + 1 + turn as u16 * 2,
+        }
+    }
+
+    pub fn row_top(&self, turn: usize) -> u16 { // first row of a guess's tiles, above the letters if they're large
+        self.guess_row(turn) - self.large as u16
+    }
+
+    pub fn tile(&self, turn: usize, index: usize) -> (u16, u16) { // where a letter of a guess goes
+        let spacing = if self.large { 6 } else { 4 };
+        (self.board.col + 2 + index as u16 * spacing, self.guess_row(turn))
+    }
+
+    pub fn tile_box(&self, turn: usize, index: usize) -> (u16, u16) { // top left of a whole tile, border and all (see tiles::tile)
+        let (col, row) = self.tile(turn, index);
+        match self.large {
+            true => (col - 2, row - 1),
+            false => (col - 1, row),
+        }
+    }
+
+    pub fn tile_size(&self) -> (u16, u16) { // columns, rows
+        match self.large {
+            true => (5, 3),
+            false => (3, 1),
+        }
+    }
+
+    pub fn separator_rows(&self) -> Vec<u16> { // the lines between (and around) the guesses, none in compact or with large tiles
+        match self.compact || self.large {
+            true => Vec::new(),
+            false => (0..=self.guesses).map(|line| self.board.row + 2 + line * 2).collect(),
+        }
+    }
+
+    pub fn text_top(&self) -> u16 { // first row of the welcome and how-to text, under the title
+        self.board.row + if self.compact { 1 } else { 2 }
+    }
+
+    pub fn message_row(&self) -> u16 {
+        self.guess_row(self.guesses as usize) + if self.compact { 1 } else { 3 } // (under the last line or tile border, and a gap)
+    }
+
+    pub fn keyboard(&self) -> (u16, u16) { // top left of the keyboard
+        let row = self.message_row() + 1;
+        match self.compact {
+            true => (self.board.centre().saturating_sub(KEYS / 2).max(1), row),
+            false => (self.board.centre().saturating_sub(KEYBOARD_WIDTH / 2), row), // the same as the board's column, unless the tiles are large
+        }
+    }
+
+    pub fn footer_row(&self) -> u16 { // daily countdown, with "press any key" on the line after
+        self.keyboard().1 + if self.compact { 1 } else { 4 }
+    }
+
+    pub fn prompt_row(&self) -> u16 {
+        self.footer_row() + 1
+    }
+
+    pub fn overlay(&self, width: u16, height: u16) -> Rect { // a box in the middle of the screen (settings, help), border and all
+        let (columns, rows) = self.size;
+        let (width, height) = (width.min(columns), height.min(rows));
+        Rect { col: (columns - width) / 2 + 1, row: (rows - height) / 2 + 1, width, height }
+    }
+
+    pub fn opponent(&self, index: usize) -> Option<u16> { // column for a race opponent, if there's room for them
+        let col = self.board.col + self.board.width + 3 + index as u16 * OPPONENT_WIDTH;
+        (col + OPPONENT_WIDTH - 1 <= self.size.0).then_some(col)
+    }
+
+    pub fn opponent_name_row(&self) -> u16 {
+        self.guess_row(1) - 1
+    }
+
+    pub fn opponent_status_row(&self) -> u16 {
+        match self.compact {
+            true => self.keyboard().1, // compact's message row gets cleared
+            false => self.guess_row(self.guesses as usize) + 2,
+        }
+    }
+
+    pub fn stats(&self) -> Rect {
+        // the stats screen. Full size is 48 wide at minimum and grows with the terminal (up to 100) so the bars can stretch out,
+        // compact fits in the same space as its board
+        let (width, height) = self.size;
+        if self.compact {
+            return Rect { col: self.board.centre().saturating_sub(KEYS / 2).max(1), row: self.board.row, width: KEYS, height: self.guesses + 5 };
+        }
+        let stats_width = width.saturating_sub(2).clamp(48, 100);
+        let col = (width.saturating_sub(stats_width) / 2).max(1); // saturating so a small terminal can't underflow
+        let row = (height / 2).saturating_sub(9).max(3); // leave room for the tabs above
+        Rect { col, row, width: stats_width, height: 19 }
+    }
+
+    // rows of the full stats screen, going down from the tabs:
+    //   tabs, numbers (three lines: number, label, second line of label), graph, history details, saved message, prompt
+    pub fn stats_tabs_row(&self) -> u16 {
+        self.stats().row.saturating_sub(2).max(1)
+    }
+
+    pub fn stats_number(&self, index: usize, count: usize) -> u16 { // column of one of count numbers spread across the top
+        let stats = self.stats();
+        stats.col + 2 + stats.width / count as u16 * index as u16
+    }
+
+    pub fn stats_graph_row(&self) -> u16 {
+        self.stats().row + if self.compact { 3 } else { 4 }
+    }
+
+    pub fn stats_bar_col(&self) -> u16 { // bars start after the "| 1 |" labels
+        self.stats().col + 5
+    }
+
+    pub fn stats_details_row(&self) -> u16 {
+        self.stats_graph_row() + self.guesses + 2
+    }
+
+    pub fn stats_detail_col(&self) -> u16 { // after the detail labels ("Openers  ...")
+        self.stats().col + 9
+    }
+
+    pub fn stats_saved_row(&self) -> u16 {
+        self.stats().row + 16
+    }
+
+    pub fn stats_prompt_row(&self) -> u16 {
+        match self.compact {
+            true => self.stats_graph_row() + self.guesses + 1,
+            false => self.stats().row + 18,
+        }
+    }
+}
+
+fn fits((width, height): (u16, u16), (min_width, min_height): (u16, u16)) -> bool {
+    width >= min_width && height >= min_height
+}
@@ -1,18 +1,34 @@
 use core::panic;
-use std::io::{self, BufRead, Write, Stdout, stdout, stdin};
-use std::collections::HashMap;
-use std::time::Duration;
-use std::path::Path;
-use std::fs::{File, OpenOptions};
-
-use termion::event::Key;
-use termion::input::TermRead;
-use termion::raw::{IntoRawMode, RawTerminal};
-use termion::screen::{AlternateScreen, IntoAlternateScreen};
-use termion::cursor::{self, DetectCursorPos};
-use termion::{clear, color};
-
-use colored::Colorize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+pub mod stats;
+pub mod history;
+pub mod config;
+pub mod daily;
+pub mod race;
+pub mod challenge;
+pub mod profile;
+pub mod recording;
+pub mod plain;
+pub mod theme;
+pub mod render;
+pub mod input;
+pub mod layout;
+pub mod tiles;
+pub mod animation;
+pub mod words;
+
+use profile::{Prefs, Profile, SavedGame};
+use recording::{Action, Recording};
+use theme::{ColourSupport, Theme, Themes};
+use render::{Renderer, Span, Style, TermionRenderer};
+use input::{read_keys, stdin_keys, Command, InputSource, Key, KeyBindings};
+use layout::{Layout, GUESSES, WORD_LENGTH};
+use tiles::{BoardStyle, Edge, Face};
+use animation::Motion;
+use words::Guesses;
 
 // five-letter word
 #[derive(Debug)]
@@ -21,27 +37,21 @@ pub struct Word {
 }
 
 impl Word {
-    pub fn try_new(word: String, valid_options: &Vec<String>) -> Result<Word, &'static str> {
+    pub fn try_new(word: String, valid_options: &Guesses) -> Result<Word, GuessError> {
 
-        // check the word is 5 alphabetic characters, then make them uppercase
-        if word.chars().count() != 5 {
-            return Err("Please choose a 5-letter word");
+        // check the word is 5 letters from a to z, then make them uppercase
+        let length = word.chars().count();
+        if length != 5 {
+            return Err(GuessError::WrongLength(length));
         }
-        if !word.chars().all(|c| c.is_alphabetic()) {
-            return Err("Please choose a real word");
+        if let Some(position) = word.chars().position(|c| !c.is_ascii_alphabetic()) {
+            return Err(GuessError::NonAlphabetic(position));
         }
         let word = word.to_uppercase();
 
         // check it is a legal word
-        let mut found: bool = false;
-        for opt in valid_options.iter() {
-            if *opt == word {
-                found = true;
-                break;
-            }
-        }
-        if !found {
-            return Err("Not in word list");
+        if !valid_options.contains(&word) {
+            return Err(GuessError::NotInWordList);
         }
         Ok(Word{contents: word})
     }
@@ -51,6 +61,77 @@ impl Word {
     }
 }
 
+// why a guess can't be played. Positions count from 0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GuessError {
+    WrongLength(usize), // how many letters it had
+    NonAlphabetic(usize), // where the first thing that isn't a letter is
+    NotInWordList,
+    HardModeMissingLetter { letter: char, position: Option<usize> }, // a revealed letter that wasn't reused: in that spot if it was green, anywhere if yellow
+}
+
+impl GuessError {
+    pub fn tile(&self) -> Option<usize> { // which tile's at fault, if it's any one in particular
+        match self {
+            GuessError::WrongLength(length) if *length < WORD_LENGTH => Some(*length), // the first one missing a letter
+            GuessError::NonAlphabetic(position) => Some(*position),
+            GuessError::HardModeMissingLetter { position, .. } => *position,
+            _ => None,
+        }
+    }
+
+    pub fn short(&self) -> String { // for the compact layout, which only has the width of the keyboard to print in
+        match self {
+            GuessError::WrongLength(_) => String::from("Need 5 letters"),
+            GuessError::NonAlphabetic(_) | GuessError::NotInWordList => String::from("Not a word"),
+            GuessError::HardModeMissingLetter { .. } => self.to_string(), // these fit already
+        }
+    }
+}
+
+impl std::fmt::Display for GuessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GuessError::WrongLength(_) => write!(f, "Please choose a 5-letter word"),
+            GuessError::NonAlphabetic(_) => write!(f, "Please choose a real word"),
+            GuessError::NotInWordList => write!(f, "Not in word list"),
+            GuessError::HardModeMissingLetter { letter, position: Some(position) } => {
+                let ordinal = match position + 1 {
+                    1 => "1st",
+                    2 => "2nd",
+                    3 => "3rd",
+                    4 => "4th",
+                    _ => "5th",
+                };
+                write!(f, "{ordinal} letter must be {letter}")
+            },
+            GuessError::HardModeMissingLetter { letter, position: None } => write!(f, "Guess must contain {letter}"),
+        }
+    }
+}
+
+impl std::error::Error for GuessError {}
+
+// the kind of game being played. Stats are kept separately for each mode (and for hard mode within it)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Normal, // random secret word every launch
+    Daily(i64), // the day's puzzle (day number from daily.rs)
+    Race, // racing other players on the LAN (see race.rs)
+    Challenge, // a word someone else picked, from a challenge code (see challenge.rs)
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str { // used in stats keys, so don't change these once released
+        match self {
+            Mode::Normal => "normal",
+            Mode::Daily(_) => "daily",
+            Mode::Race => "race",
+            Mode::Challenge => "challenge",
+        }
+    }
+}
+
 // represents letter colours, for use in array
 #[derive(Copy, Clone)]
 enum Letter {
@@ -59,432 +140,1018 @@ enum Letter {
     Grey,
 }
 
-pub struct Board {
+impl Letter {
+    fn pattern_char(&self) -> char { // how a tile is sent to other players in a race (see race.rs)
+        match self {
+            Letter::Green => 'G',
+            Letter::Yellow => 'Y',
+            Letter::Grey => '.',
+        }
+    }
+}
+
+// letter arrangement of the on-screen keyboard, so it matches the one on the desk
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Azerty,
+    Qwertz,
+    Dvorak,
+    Colemak,
+}
+
+impl KeyboardLayout {
+    const ALL: [KeyboardLayout; 5] = [KeyboardLayout::Qwerty, KeyboardLayout::Azerty, KeyboardLayout::Qwertz, KeyboardLayout::Dvorak, KeyboardLayout::Colemak];
+
+    pub fn name(&self) -> &'static str { // saved in profile preferences
+        match self {
+            KeyboardLayout::Qwerty => "qwerty",
+            KeyboardLayout::Azerty => "azerty",
+            KeyboardLayout::Qwertz => "qwertz",
+            KeyboardLayout::Dvorak => "dvorak",
+            KeyboardLayout::Colemak => "colemak",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<KeyboardLayout> {
+        KeyboardLayout::ALL.into_iter().find(|l| l.name() == text.trim().to_lowercase())
+    }
+
+    pub fn next(&self) -> KeyboardLayout { // for cycling through them in the settings menu
+        let index = KeyboardLayout::ALL.iter().position(|l| l == self).unwrap_or(0);
+        KeyboardLayout::ALL[(index + 1) % KeyboardLayout::ALL.len()]
+    }
+
+    fn sequence(&self) -> &'static str {
+        // whitespace is a new row, _ is a space for alignment (see Keyboard::format)
+        match self {
+            KeyboardLayout::Qwerty => " _QWERTYUIOP __ASDFGHJKL ____ZXCVBNM",
+            KeyboardLayout::Azerty => " _AZERTYUIOP __QSDFGHJKLM ____WXCVBN",
+            KeyboardLayout::Qwertz => " _QWERTZUIOP __ASDFGHJKL ____YXCVBNM",
+            KeyboardLayout::Dvorak => " _____PYFGCRL __AOEUIDHTNS ___QJKXBMWVZ",
+            KeyboardLayout::Colemak => " _QWFPGJLUY __ARSTDHNEIO ____ZXCVBKM",
+        }
+    }
+}
+
+pub struct Board<R: Renderer = TermionRenderer> {
+    pub mode: Mode,
+    pub profile: Profile, // whose stats, preferences and saved games these are (see profile.rs)
+    pub timezone: daily::Timezone, // for daily mode's countdown to the next puzzle
     pub hard: bool, // hard mode?
-    pub contrast: bool, // high-contrast mode?
+    pub theme: Theme, // tile colours
+    pub themes: Themes, // what the theme key cycles through
+    pub colours: ColourSupport, // what the terminal can show
+    pub layout: KeyboardLayout, // on-screen keyboard
+    style: BoardStyle, // how the tiles are drawn (see tiles.rs), change it with set_style
+    pub motion: Motion, // animation speed, or none (see animation.rs)
+    pub bindings: KeyBindings, // which keys quit and open the settings and help (see input.rs)
+    pub defaults: Prefs, // settings for profiles that haven't chosen their own, from the config file
     pub win: bool, // did you win?
     pub turn: usize, // what turn is it? (turn zero is for board setup)
     pub secret_word: Word,
     pub guesses: Vec<Word>, // all words that have been guessed
     keyboard: Keyboard, // holds info about what letters have been guessed
-    screen: RawTerminal<AlternateScreen<Stdout>>, // go into alternate screen in raw mode when board is constructed
-    coord: (u16, u16), // column, row of board top left corner (where W O R D L E is printed) and column of left board border
+    pub race: Option<race::Race>, // other players' progress, in race mode
+    race_drawn: Option<u64>, // race generation last drawn in the opponent panel (None while the panel isn't on screen)
+    screen: R, // where everything gets drawn (see render.rs)
+    keys: Box<dyn InputSource>, // key presses, read on their own thread so the screen can update while waiting for input
+    recording: Option<Recording>, // this game's keys and messages, saved when it ends (see recording.rs)
+    replay: Option<Recording>, // the recording being played back, if this is a replay rather than a game
+    geometry: Layout, // where everything goes on screen (see layout.rs), redone whenever the terminal's resized
+    cursor: (u16, u16), // where the typing cursor belongs, so it can be put back after printing elsewhere
+    paused: bool, // terminal's been made too small to draw in, waiting for it to grow again
+    view: View, // what's on screen, so it can all be drawn again after a resize
+    overlay: Option<Overlay>, // settings or help, drawn over the view
+    message: String, // last message under the board (empty if it's been cleared)
+    typed: String, // letters entered on the current row so far
+    caret: usize, // which of them the cursor's on (typed's length when it's after the last one)
+    flagged: Option<usize>, // the tile that got the typed guess rejected (see GuessError::tile), until its letters change
+    stats_screen: Option<StatsScreen>, // everything the stats screen shows, once it's up
+    pending: VecDeque<Key>, // keys pressed during an animation, still to be handled
+}
+
+// the screens the board can be showing
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum View {
+    Welcome,
+    Game,
+    GameOver, // board with the win/failure message and "press any key"
+    Stats,
+}
+
+// boxes that go over whatever's on screen, until they're closed
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Overlay {
+    Settings(usize, Option<&'static str>), // the selected setting, and how saving the defaults went (until the next key)
+    Help,
+}
+
+// what the settings menu can change. All of them are remembered for the profile
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Setting {
+    Hard,
+    Theme,
+    Keyboard,
+    Style,
+    Animations,
+    Profile, // only before the game starts
+    Defaults, // not a setting, saves the others to the config file for every profile that hasn't chosen its own
+}
+
+impl Setting {
+    fn name(&self) -> &'static str {
+        match self {
+            Setting::Hard => "Hard mode",
+            Setting::Theme => "Theme",
+            Setting::Keyboard => "Keyboard",
+            Setting::Style => "Board",
+            Setting::Animations => "Animations",
+            Setting::Profile => "Profile",
+            Setting::Defaults => "Save as defaults",
+        }
+    }
+}
+
+struct StatsScreen {
+    book: stats::StatsBook,
+    records: Vec<history::GameRecord>,
+    tabs: Vec<String>,
+    selected: usize,
+    saved: Option<std::io::Result<Option<std::path::PathBuf>>>, // None for a replay
+    history_error: Option<String>, // the stats saved but this game isn't in the history
+    prompt: bool, // whether "press any key" is up yet
 }
 
 impl Board {
     pub fn new(secret_word: Word) -> Board {
+        // read keys on a separate thread. Blocking on stdin here would stop anything else (like opponents) being drawn
+        // note this means termion's cursor_pos() can't be used any more: it would be racing this thread for the reply
+        let (sender, keys) = mpsc::channel();
+        read_keys(sender, None);
+        Board::with_io(secret_word, TermionRenderer::new(), Box::new(keys)) // go into alternate screen in raw mode
+    }
+
+    pub fn replay(recording: Recording, bindings: KeyBindings) -> Board {
+        // a board that plays back a recorded game: the keys come from the recording, at the pace they were pressed
+        // the keyboard only gets a say in quitting (the player's quit key, given in bindings, or Esc). The recorded keys
+        // mean whatever the bindings were when the game was played, so the board goes by those
+        let (sender, keys) = mpsc::channel();
+        let quit = recording.bindings.keys(Command::Quit)[0]; // (set always leaves a command at least one key)
+        read_keys(sender.clone(), Some((bindings.keys(Command::Quit), quit)));
+        recording.play(sender);
+        let mut board = Board::with_io(Word { contents: recording.secret.clone() }, TermionRenderer::new(), Box::new(keys));
+        board.mode = recording.mode;
+        board.hard = recording.hard;
+        board.theme = board.themes.get_or_default(&recording.theme);
+        board.layout = recording.layout;
+        board.bindings = recording.bindings.clone();
+        board.replay = Some(recording);
+        board
+    }
+}
+
+impl<R: Renderer> Board<R> {
+    pub fn with_io(secret_word: Word, screen: R, keys: Box<dyn InputSource>) -> Board<R> {
+        // any screen and any keys, e.g. a GridRenderer and a list of keys to see what a game would look like
 
         // figure out where to print the board on screen
-        let (mut col, mut row) = termion::terminal_size().unwrap();
-        match (col / 2).checked_sub(10) { // ensures no overflow if terminal is small
-            Some(n) => col = n,
-            None => col = 0,
-        }
-        match (row / 2).checked_sub(9) { // can change the "checked sub" arguments as a master "slider" for board position
-            Some(n) => row = n,
-            None => row = 0,
-        }
+        let geometry = Layout::new(screen.size(), WORD_LENGTH, GUESSES, BoardStyle::default().large_tiles());
+        let (col, row) = (geometry.board().col, geometry.board().row);
 
         Board {
+            mode: Mode::Normal,
+            profile: Profile::default(),
+            timezone: daily::Timezone::Local,
             hard: false,
-            contrast: false,
+            theme: Themes::default().get_or_default("dark"),
+            themes: Themes::default(),
+            colours: ColourSupport::detect(),
+            layout: KeyboardLayout::Qwerty,
+            style: BoardStyle::default(),
+            motion: Motion::default(),
+            bindings: KeyBindings::default(),
+            defaults: Prefs::default(),
             win: false,
             turn: 0,
             secret_word,
             guesses: vec![],
             keyboard: Keyboard::initialize(),
-            screen: stdout().into_alternate_screen().unwrap().into_raw_mode().unwrap(),
-            coord: (col, row),
+            race: None,
+            race_drawn: None,
+            screen,
+            keys,
+            recording: None,
+            replay: None,
+            geometry,
+            cursor: (col, row),
+            paused: false,
+            view: View::Welcome,
+            overlay: None,
+            message: String::new(),
+            typed: String::new(),
+            caret: 0,
+            flagged: None,
+            stats_screen: None,
+            pending: VecDeque::new(),
         }
     }
 
-    pub fn welcome(&mut self) {
-        let (col, row) = self.coord;
-
-        let mut how_to_display = false; // whether or not the "how-to" is what's on screen
-        'outer: loop {
-            // print game title
-            write!(self.screen, "{}{}{}W O R D L E", // should print in the same place it will be for the board
-                termion::clear::All,
-                cursor::Hide,
-                cursor::Goto(col + 5, row)
-            ).unwrap();
-
-            // print key commands
-            let help = "Guess by typing a word\nand pressing Enter\n\nPress ` to Exit,\n1 for Hard Mode,\n2 for High Contrast\n3 for How To Play\n\nPress Enter to Start Game";
-            let help_row = row + 2;
-            for (line, message) in help.lines().enumerate() {
-                write!(self.screen, "{}{message}",
-                    cursor::Goto(col + 10 - (message.len() as u16 / 2), help_row + line as u16),
-                ).unwrap();
-            }
-            self.screen.flush().unwrap();
-
-            // press Enter to start; allow activating high contrast/hard mode before game start
-            let input = stdin();
-            for key in input.keys() {
-                match key.unwrap() {
-                    Key::Char('`') => {
-                        self.print_welcome_msg(&format!("\r{}", termion::clear::CurrentLine));
-                        self.print_welcome_msg("Exiting");
-                        std::thread::sleep(std::time::Duration::from_millis(555));
-                        panic!("exiting program"); // for debugging
-                    },
-                    Key::Char('1') => { // enable hard mode
-                        self.print_welcome_msg(&format!("\r{}", termion::clear::CurrentLine));
-                        if self.hard { // if it's already enabled
-                            self.print_welcome_msg("Hard mode already enabled");
-                        } else if self.guesses.is_empty() { // enable only if you haven't guessed yet
-                            self.hard = true;
-                            self.print_welcome_msg("Hard mode enabled");
-                        } else {
-                            self.print_welcome_msg("Cannot enable hard mode"); // actual message is "Hard mode can only be enabled at the start of a round" but that's long and could make terminal panic  
-                        }
-                    },
-                    Key::Char('2') => {
-                        self.print_welcome_msg(&format!("\r{}", termion::clear::CurrentLine));
-                        if self.contrast {
-                            self.print_welcome_msg("High contrast mode already enabled");
-                        } else if self.guesses.is_empty() { // only if you haven't guessed yet (else you'd have to redraw coloured rows)
-                            self.contrast = true;
-                            self.print_welcome_msg("High contrast mode enabled");
-                        } else {
-                            self.print_welcome_msg("Cannot enable high contrast mode");
-                        }
-                    },
-                    Key::Char('3') => {
-                        if !how_to_display {
-                            how_to_display = true;
-                            self.print_welcome_msg(&format!("{}", termion::clear::All));
-                            let how_to = "HOW TO PLAY\n\nGuess the Wordle in 6 tries\nEach guess must be a valid 5-letter word\n\nThe colour of the tiles will\nchange to show how close\nyour guess was to the word";
-                            let how_to_row = row + 2;
-                            for (line, message) in how_to.lines().enumerate() {
-                                write!(self.screen, "{}{message}",
-                                    cursor::Goto(col + 10 - (message.len() as u16 / 2), how_to_row + line as u16),
-                                ).unwrap();
-                            }
-                        } else { // if how-to is already on-screen
-                            how_to_display = false;
-                            continue 'outer; // this will send you back to the top of the outer loop and redraw menu
-                        }
-                        self.screen.flush().unwrap();
-                    },
-                    Key::Char('\n') => {
-                        break 'outer; // pressing enter breaks whole loop and moves on
-                    },
-                    _ => (),
-                }
+    pub fn screen(&self) -> &R { // e.g. to read a GridRenderer back
+        &self.screen
+    }
+
+    pub fn screen_mut(&mut self) -> &mut R {
+        &mut self.screen
+    }
+
+    fn next_key(&mut self) -> Key {
+        // waits for a key press, keeping anything live on screen up to date in the meantime
+        if let Some(key) = self.pending.pop_front() {
+            return key; // (already recorded)
+        }
+        loop {
+            match self.keys.next_key(Duration::from_millis(50)) {
+                Ok(key) if self.paused && self.bindings.command(key) != Some(Command::Quit) => continue, // nothing to type into while the board's hidden
+                Ok(key) => {
+                    if let Some(recording) = &mut self.recording {
+                        recording.log(Action::Key(key));
+                    }
+                    return key;
+                },
+                Err(RecvTimeoutError::Timeout) => self.tick(),
+                Err(RecvTimeoutError::Disconnected) => panic!("keyboard input closed"),
             }
         }
+    }
 
-        write!(self.screen, "{}", cursor::Show).unwrap();
-        self.screen.flush().unwrap();
+    pub fn wait_for_key(&mut self) {
+        // suspends program while waiting for user to press a key
+        self.next_key();
     }
 
-    pub fn check_guess(&self) -> bool {
-        if let Some(guess) = self.guesses.last() { // return true if most recent guess matches the secret word
-            return guess.contents() == self.secret_word.contents();
-        } else {
-            return false
+    fn tick(&mut self) {
+        // resizes don't come through as events, so look for a new size every tick
+        let size = self.screen.size();
+        if size != self.geometry.size {
+            self.resize(size);
+        }
+        if self.paused {
+            return;
+        }
+
+        // redraw the opponent panel if anyone's made progress since it was last drawn
+        if let (Some(race), Some(drawn)) = (&self.race, self.race_drawn) {
+            if race.generation() != drawn {
+                self.draw_opponents();
+            }
         }
     }
 
-    fn check_matches(&self, guess: &Word) -> [Letter; 5] { // allows checking against guess you specify, not just most recent
-        let mut match_counter: HashMap<char, usize> = HashMap::new();
-        let mut letter_colours: [Letter; 5] = [Letter::Grey; 5];
-        let mut secret_word: [char; 5] = ['_'; 5];
-        let mut guess_word: [char; 5] = ['_'; 5];
-        for (index, guess_letter) in guess.contents().char_indices() {
-            guess_word[index] = guess_letter;
+    fn resize(&mut self, size: (u16, u16)) {
+        // the terminal's changed size: put everything back in the middle, or ask for more room if it's too small now
+        self.geometry = Layout::new(size, WORD_LENGTH, GUESSES, self.style.large_tiles());
+        if !Layout::fits(size, WORD_LENGTH, GUESSES) {
+            let (width, height) = Layout::smallest(WORD_LENGTH, GUESSES);
+            self.paused = true;
+            self.screen.clear();
+            self.screen.show_cursor(false);
+            self.screen.print(1, 1, "Please resize the terminal");
+            self.screen.print(1, 2, &format!("to at least {width} x {height}"));
+            self.screen.flush();
+            return;
         }
+        self.paused = false;
+        self.place_cursor();
+        self.repaint();
+    }
 
-        // check GREEN matches (same-index matches)
-        for (index, secret_letter) in self.secret_word.contents().char_indices() {
-            secret_word[index] = secret_letter;
-            if secret_letter == guess_word[index] {
-                letter_colours[index] = Letter::Green;
-                match_counter.entry(secret_letter).and_modify(|count| *count += 1).or_insert(1);
-            }
+    fn place_cursor(&mut self) {
+        // the cursor goes back after the letters typed so far (anywhere else, it's hidden)
+        let board = self.geometry.board();
+        self.cursor = match self.view {
+            View::Game if self.turn > self.guesses.len() => self.geometry.tile(self.turn, self.caret),
+            _ => (board.col, board.row),
+        };
+    }
+
+    fn draw_keyboard(&mut self) {
+        let (col, row) = self.geometry.keyboard();
+        match self.geometry.compact {
+            true => self.keyboard.draw_inline(&mut self.screen, (col, row), &self.theme, self.colours, self.layout),
+            false => self.keyboard.draw(&mut self.screen, (col, row), &self.theme, self.colours, self.layout),
         }
-        
-        // check YELLOW matches (a secret word's letter exists in guess word and is still GREY)
-        // AND the amount of that letter in the secret word is MORE than the number that have been logged in the map already
-        for secret_letter in self.secret_word.contents().chars() {
-            for (index, guess_letter) in guess.contents().char_indices() {
-                if guess_letter == secret_letter
-                && self.secret_word.contents().chars().filter(|s| s == &secret_letter).count() > *match_counter.get(&secret_letter).unwrap_or_else(|| &0) {
-                    if let Letter::Grey = letter_colours[index] {
-                        letter_colours[index] = Letter::Yellow;
-                        match_counter.entry(secret_letter).and_modify(|count| *count += 1).or_insert(1);
+    }
+
+    fn repaint(&mut self) {
+        // draws whatever's meant to be on screen from scratch
+        match self.view {
+            View::Welcome => self.draw_welcome(),
+            View::Game | View::GameOver => {
+                self.redraw();
+                if self.view == View::GameOver {
+                    self.screen.show_cursor(false);
+                    self.draw_game_over();
+                } else if self.caret < WORD_LENGTH {
+                    self.screen.show_cursor(true);
+                }
+            },
+            View::Stats => {
+                if let Some(stats_screen) = self.stats_screen.take() {
+                    self.draw_stats(&stats_screen);
+                    if stats_screen.prompt {
+                        self.draw_exit_prompt(&stats_screen);
                     }
+                    self.stats_screen = Some(stats_screen);
                 }
+                return; // no board messages here
+            },
+        }
+        let message = self.message.clone();
+        self.show_msg(&message);
+        self.draw_overlay();
+    }
+
+    fn draw_opponents(&mut self) {
+        // compact panel to the right of the board: every opponent's rows as coloured blocks, never letters
+        let Some(race) = &self.race else { return };
+        let opponents = race.opponents();
+        self.race_drawn = Some(race.generation());
+
+        for (index, player) in opponents.iter().enumerate() {
+            let Some(player_col) = self.geometry.opponent(index) else {
+                break; // no room for any more
+            };
+            let name: String = player.name.chars().take(7).collect();
+            self.screen.print(player_col, self.geometry.opponent_name_row(), &format!("{name:<7}"));
+            for line in 0..GUESSES {
+                let blocks: Vec<Span> = match player.rows.get(line) {
+                    Some(pattern) => pattern.chars().map(|tile| match tile { // shapes as well as colours, since grey is hard to see
+                        'G' => self.theme.paint("■", Letter::Green, self.colours),
+                        'Y' => self.theme.paint("■", Letter::Yellow, self.colours),
+                        _ => self.theme.paint("□", Letter::Grey, self.colours),
+                    }).collect(),
+                    None => vec![Span::plain("·····")],
+                };
+                self.screen.print_spans(player_col, self.geometry.guess_row(line + 1), &blocks);
             }
+            let status = match (player.result, player.connected) {
+                (Some(true), _) => format!("won {}", player.rows.len()),
+                (Some(false), _) => String::from("lost"),
+                (None, false) => String::from("left"),
+                (None, true) => String::new(),
+            };
+            self.screen.print(player_col, self.geometry.opponent_status_row(), &format!("{status:<7}"));
         }
-        letter_colours
+
+        self.screen.move_cursor(self.cursor.0, self.cursor.1);
+        self.screen.flush();
     }
 
-    fn format(&mut self, colours: &[Letter; 5]) -> String {
+    pub fn set_profile(&mut self, profile: Profile) {
+        // switch to someone's profile, taking on their preferences
+        let prefs = Prefs::load(&profile, &self.defaults);
+        self.hard = prefs.hard;
+        self.theme = self.themes.get_or_default(prefs.theme.as_deref().unwrap_or(&self.themes.default));
+        self.layout = prefs.layout;
+        self.set_style(prefs.style);
+        self.motion = prefs.motion;
+        self.profile = profile;
+    }
+
+    pub fn set_style(&mut self, style: BoardStyle) {
+        // the board's size depends on the style, so everything gets laid out again
+        self.style = style;
+        self.geometry = Layout::new(self.geometry.size, WORD_LENGTH, GUESSES, style.large_tiles());
+    }
+
+    fn save_prefs(&self) {
+        if self.replay.is_some() {
+            return; // a replay toggling things shouldn't change anyone's settings
+        }
+        // errors are ignored: the change still applies to this game, it just won't be remembered
+        let _ = self.prefs().save(&self.profile);
+    }
+
+    fn prefs(&self) -> Prefs {
+        Prefs { hard: self.hard, theme: Some(self.theme.name.clone()), layout: self.layout, style: self.style, motion: self.motion }
+    }
+
+    fn save_game(&self) -> bool {
+        // keep an unfinished game so it can be carried on next time (see profile.rs), returns whether there was one to keep
+        if self.guesses.is_empty() || self.win || self.race.is_some() || self.replay.is_some() {
+            return false;
+        }
+        let saved = SavedGame {
+            secret: self.secret_word.contents().clone(),
+            guesses: self.guesses.iter().map(|g| g.contents().clone()).collect(),
+            hard: self.hard,
+            day: match self.mode {
+                Mode::Daily(day) => Some(day),
+                _ => None,
+            },
+        };
+        saved.save(&self.profile, self.mode).is_ok()
+    }
+
+    pub fn resume(&mut self) {
+        // carries on from a game this profile quit part way through, replaying its guesses onto the (already drawn) board
+        let saved = match &self.replay {
+            Some(replay) => replay.resumed.clone(), // whatever the recorded game picked up, not what's saved now
+            None => SavedGame::load(&self.profile, self.mode, self.secret_word.contents()),
+        };
+        let Some(saved) = saved else { return };
+        if self.replay.is_none() {
+            SavedGame::clear(&self.profile, self.mode); // it's in play again, it'll be saved again if they quit again
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.resumed = Some(saved.clone());
+        }
+        self.secret_word = Word { contents: saved.secret };
+        self.hard = saved.hard;
+        for guess in saved.guesses {
+            self.guesses.push(Word { contents: guess }); // checked when they were first guessed
+            self.turn = self.guesses.len();
+            self.score_guess();
+        }
+        self.redraw(); // all at once, no need to watch them turn over again
+        self.cursor = self.geometry.tile(self.turn + 1, 0); // where the next guess goes, for print_msg
+        self.print_msg("Saved game resumed");
+    }
+
+    fn save_recording(&mut self, timestamp: u64) {
+        // errors are ignored, a game shouldn't fail because its recording couldn't be kept
+        if let Some(recording) = self.recording.take() {
+            let _ = recording::save(&self.profile, &recording, timestamp);
+        }
+    }
+
+    pub fn stats_key(&self) -> String { // which set of stats this game counts towards
+        stats::key(self.mode.name(), self.hard)
+    }
+
+    pub fn welcome(&mut self) {
+        // everything from here on is recorded (unless it's a replay already)
+        if self.replay.is_none() {
+            self.recording = Some(Recording::new(self.mode, self.secret_word.contents(), self.hard, &self.theme.name, self.layout, self.bindings.clone()));
+        }
+
+        self.view = View::Welcome;
+        self.message.clear();
+        self.draw_welcome();
+
+        // press Enter to start; settings (theme, hard mode, profile...) can be changed before the game starts
+        loop {
+            match self.next_key() {
+                key if self.bindings.command(key) == Some(Command::Quit) => {
+                    self.clear_msg();
+                    self.print_welcome_msg("Exiting");
+                    std::thread::sleep(std::time::Duration::from_millis(555));
+                    panic!("exiting program"); // for debugging
+                },
+                key if self.bindings.command(key) == Some(Command::Settings) => self.settings(),
+                key if self.bindings.command(key) == Some(Command::Help) => self.help(),
+                Key::Char('\n') => {
+                    break; // pressing enter breaks the loop and moves on
+                },
+                _ => (),
+            }
+        }
+
+        self.message.clear();
+        self.screen.show_cursor(true);
+        self.screen.flush();
+    }
+
+    fn draw_welcome(&mut self) {
+        let (col, row) = self.geometry.title();
+        let top = self.geometry.text_top();
+
+        // print game title
+        self.screen.clear();
+        self.screen.show_cursor(false);
+        self.screen.print(col, row, "W O R D L E"); // should print in the same place it will be for the board
+
+        // print key commands (a shorter list if there's not much room)
+        let (quit, settings, help) = (self.bindings.label(Command::Quit), self.bindings.label(Command::Settings), self.bindings.label(Command::Help));
+        if self.geometry.compact {
+            let help = format!("Enter to start, {quit} exit\n{settings} settings  {help} help\nProfile: {}", self.profile.name());
+            self.draw_centred(&help, top);
+            return;
+        }
+        let help = format!("Guess by typing a word\nand pressing Enter\n\nPress Enter to Start Game\n{settings} for Settings\n{help} for Help\n{quit} to Exit\n\nProfile: {}", self.profile.name());
+        self.draw_centred(&help, top);
+    }
+
+    fn draw_centred(&mut self, text: &str, top: u16) { // lines of text centred on the board, from row top down
+        let board = self.geometry.board();
+        for (line, message) in text.lines().enumerate() {
+            self.screen.print(board.centred(message), top + line as u16, message);
+        }
+        self.screen.flush();
+    }
+
+    pub fn check_guess(&self) -> bool {
+        if let Some(guess) = self.guesses.last() { // return true if most recent guess matches the secret word
+            guess.contents() == self.secret_word.contents()
+        } else {
+            false
+        }
+    }
+
+    fn check_matches(&self, guess: &Word) -> [Letter; 5] { // allows checking against guess you specify, not just most recent
+        score(&self.secret_word, guess)
+    }
+
+    fn score_guess(&mut self) -> [Letter; 5] {
 
         // figures out what colours to display for the board and keyboard elements, but does not actually print to screen
-        // returns a formatted String from letter colours array and also updates the keyboard colours
+        // returns the latest guess's letter colours, after updating the keyboard colours (and telling anyone racing)
 
         let guess = self.guesses.last().unwrap(); // safe because not calling until a guess has been made
-        let mut to_print = String::new();
+        if let Some(recording) = &mut self.recording {
+            recording.log(Action::Guess(guess.contents().clone()));
+        }
+        let colours = self.check_matches(guess);
         for (index, letter) in guess.contents().char_indices() {
-            to_print = format!("{to_print}| ");
             match colours[index] {
                 Letter::Green => {
-                    if self.contrast {
-                        to_print = format!("{}{} ", to_print, String::from(letter).bright_magenta());                        
-                    } else {
-                        to_print = format!("{}{} ", to_print, String::from(letter).bright_green());
-                    }
                     self.keyboard.guessed_letters.insert(letter, Letter::Green); // updating keyboard colours
                 },
-                Letter::Yellow => {
-                    if self.contrast {
-                        to_print = format!("{}{} ", to_print, String::from(letter).bright_cyan());
-                    } else {
-                        to_print = format!("{}{} ", to_print, String::from(letter).bright_yellow());
-                    }
-                    // if the letter is not already in the keyboard as yellow or green
-                    if let &Letter::Grey = self.keyboard.guessed_letters.get(&letter).unwrap_or(&Letter::Grey) {
-                        self.keyboard.guessed_letters.insert(letter, Letter::Yellow);    
-                    }
-                },
-                Letter::Grey => {
-                    to_print = format!("{}{} ", to_print, String::from(letter).truecolor(10, 10, 10));
+                Letter::Yellow | Letter::Grey => {
                     // if the letter is not already in the keyboard as yellow or green
                     if let &Letter::Grey = self.keyboard.guessed_letters.get(&letter).unwrap_or(&Letter::Grey) {
-                        self.keyboard.guessed_letters.insert(letter, Letter::Grey);    
+                        self.keyboard.guessed_letters.insert(letter, colours[index]);
                     }
                 },
             }
         }
 
-        format!("{}|", to_print)
+        // let the other racers know how it went (colours only)
+        if let Some(race) = &mut self.race {
+            race.send_row(&colours.iter().map(|l| l.pattern_char()).collect::<String>());
+        }
+        self.typed.clear(); // it's a guess now, the next row starts empty
+        self.caret = 0;
+        self.flagged = None;
+        colours
     }
-    
-    pub fn draw(&mut self) {
-        if self.turn == 0 { // "turn zero" prints the board blank, centred
 
-            let (col, row) = self.coord; // Goto() uses col/row
-
-            // print game title
-            write!(self.screen, "{}{}W O R D L E",
-                termion::clear::All,
-                cursor::Goto(col + 5, row)
-            ).unwrap();
-
-            // print board "frame"
-            let board_top = row + 2; // row of top of board
-            for offset in 0..=5 {
-                write!(self.screen, "{}---------------------{}|   |   |   |   |   |",
-                    cursor::Goto(col, board_top + offset * 2),
-                    cursor::Goto(col, board_top + offset * 2 + 1)
-                ).unwrap();
-            }
-            write!(self.screen, "{}---------------------",
-                cursor::Goto(col, board_top + 12)
-            ).unwrap();
+    fn paint_row(&self, letters: &str, faces: &[Face]) -> Vec<Vec<Span>> {
+        // a row as it appears on the board, e.g. "| C | R | A | N | E |" in the theme's colours
+        tiles::row(self.style, self.geometry.large, letters, faces, &self.theme, self.colours)
+    }
 
-            // print full keyboard
-            let keyboard_top = row + 17; // row of top of keyboard
-            write!(self.screen, "{}", self.keyboard.format((col, keyboard_top), self.contrast)).unwrap();
+    fn print_row(&mut self, turn: usize, lines: &[Vec<Span>]) {
+        let (col, top) = (self.geometry.board().col, self.geometry.row_top(turn));
+        for (line, spans) in lines.iter().enumerate() {
+            self.screen.print_spans(col, top + line as u16, spans);
+        }
+    }
+
+    pub fn redraw(&mut self) {
+        // repaints the whole board from the stored guesses (rather than turning over the newest row like draw does),
+        // e.g. after switching theme, including whatever's been entered on the current row so far
+        self.draw_frame();
+        self.screen.move_cursor(self.cursor.0, self.cursor.1);
+        self.screen.flush();
+    }
 
-            // flush screen buffer
-            self.screen.flush().unwrap();
+    fn draw_frame(&mut self) {
+        // title, grid and keyboard (plus the opponents in a race)
+        self.screen.clear();
+        self.draw_grid();
 
-        } else { // turns 1-6
+        // print full keyboard
+        self.draw_keyboard();
+
+        // flush screen buffer
+        self.screen.flush();
 
-            let (col, row) = self.coord;
+        // in a race, opponents go alongside the board from now on
+        self.draw_opponents();
+    }
+
+    fn draw_grid(&mut self) {
+        // the title and every row as things stand: guesses in their colours, the one being typed, and blanks after
+        let (title_col, title_row) = self.geometry.title();
+        let col = self.geometry.board().col;
+
+        // print game title
+        self.screen.print(title_col, title_row, "W O R D L E");
+
+        // print board "frame" (compact and large tiles have just the rows, no lines between them)
+        let separators = self.geometry.separator_rows();
+        for (line, row) in separators.iter().enumerate() {
+            let edge = match line {
+                0 => Edge::Top,
+                _ if line + 1 == separators.len() => Edge::Bottom,
+                _ => Edge::Between,
+            };
+            self.screen.print(col, *row, &tiles::edge(self.style, edge));
+        }
+        for turn in 1..=GUESSES {
+            let lines = match self.guesses.get(turn - 1) {
+                Some(guess) => self.paint_row(guess.contents(), &self.check_matches(guess).map(Face::Scored)),
+                None if turn == self.turn => self.paint_row(&self.typed.to_uppercase(), &self.typed_faces()), // mid-guess
+                None => self.paint_row("", &[Face::Plain; 5]),
+            };
+            self.print_row(turn, &lines);
+        }
+    }
 
-            // check matches and format the letter colours to print
-            let last_guess = self.guesses.last().unwrap(); // unwrap is safe here
-            let letter_colours = self.check_matches(last_guess);
-            let to_print = self.format(&letter_colours);
+    pub fn draw(&mut self) {
+        if self.turn == 0 { // "turn zero" prints the board blank, centred
+            self.view = View::Game;
+            self.draw_frame();
+        } else { // turns 1-6
 
-            // update keyboard display
-            let keyboard_top = row + 17;
-            write!(self.screen, "{}", self.keyboard.format((col, keyboard_top), self.contrast)).unwrap();
+            // check matches and work out the letter colours
+            let colours = self.score_guess();
+
+            // turn the tiles over one at a time, then show the colours on the keyboard too
+            let (turn, letters) = (self.turn, self.guesses[self.turn - 1].contents().clone());
+            self.animate(animation::flip_frames(), animation::FRAME, |board, frame| {
+                let lines = board.paint_row(&letters, &animation::flip(&colours, frame));
+                board.print_row(turn, &lines);
+            });
+            self.draw_keyboard();
+            self.screen.flush();
+
+            // a win gets a little celebration
+            if self.check_guess() {
+                self.animate(animation::bounce_frames(), animation::FRAME, |board, frame| {
+                    board.draw_grid(); // puts back anything the last frame's tiles were covering
+                    for (index, letter) in letters.chars().enumerate().filter(|(index, _)| animation::bounce(frame, *index)) {
+                        let tile = tiles::tile(board.style, board.geometry.large, letter, Face::Scored(colours[index]), &board.theme, board.colours);
+                        let (col, top) = board.geometry.tile_box(turn, index);
+                        for (line, spans) in tile.iter().enumerate() {
+                            board.screen.print_spans(col, top + line as u16 - 1, spans);
+                        }
+                        let (width, height) = board.geometry.tile_size();
+                        board.screen.print(col, top + height - 1, &" ".repeat(width as usize)); // where it jumped from
+                    }
+                });
+            }
+        }
+    }
 
-            // move cursor to appropriate board row top prep for scrolling coloured guess
-            let guess_row = row + 1;
-            write!(self.screen, "{}{}",
-                cursor::Goto(col, guess_row + self.turn as u16 * 2), // go to start of turn row
-                cursor::Show
-            ).unwrap();
+    pub fn reject(&mut self, error: &GuessError) {
+        // a guess that can't be played: say why and shake the row (its letters stay there to be edited,
+        // with the tile that's wrong picked out if there is one)
+        let msg = if self.geometry.compact { error.short() } else { error.to_string() };
+        self.print_msg(&msg);
+        self.flagged = error.tile();
+        let (turn, letters) = (self.turn, self.typed.to_uppercase());
+        self.animate(animation::shake_frames(), animation::FRAME, |board, frame| {
+            let lines = board.paint_row(&letters, &[Face::Plain; 5]);
+            let board_area = board.geometry.board();
+            let col = (board_area.col as i16 + animation::shake(frame)).max(1) as u16;
+            let top = board.geometry.row_top(turn);
+            for (line, spans) in lines.iter().enumerate() {
+                let row = top + line as u16;
+                board.screen.print(board_area.col.saturating_sub(2).max(1), row, &" ".repeat(board_area.width as usize + 4));
+                board.screen.print_spans(col, row, spans);
+            }
+        });
+    }
 
-            // flush screen buffer
-            self.screen.flush().unwrap();
+    fn animate(&mut self, frames: usize, frame_time: Duration, mut draw: impl FnMut(&mut Self, usize)) {
+        // draws frames 0 up to frames - 1 of an animation (see animation.rs) with a pause after each
+        // a key press jumps straight to the last frame, and is kept for whatever's waiting for a key next
+        // (so with keys already waiting, or animations turned off, only the last frame gets drawn).
+        // A resize stops it where it is, since everything's drawn again (finished) for the new size anyway
+        self.screen.show_cursor(false);
+        if !self.motion.animate || !self.pending.is_empty() {
+            if frames > 0 {
+                draw(self, frames - 1);
+            }
+            self.screen.move_cursor(self.cursor.0, self.cursor.1);
+            self.screen.flush();
+            return;
+        }
+        let frame_time = self.motion.scale(frame_time);
+        for frame in 0..frames {
+            draw(self, frame);
+            self.screen.flush();
+            if frame + 1 == frames {
+                break;
+            }
+            match self.keys.next_key(frame_time) {
+                Ok(key) => {
+                    if let Some(recording) = &mut self.recording {
+                        recording.log(Action::Key(key));
+                    }
+                    self.pending.push_back(key);
+                    draw(self, frames - 1);
+                    self.screen.flush();
+                    break;
+                },
+                Err(RecvTimeoutError::Timeout) => {
+                    let size = self.geometry.size;
+                    self.tick();
+                    if self.geometry.size != size {
+                        break;
+                    }
+                },
+                Err(RecvTimeoutError::Disconnected) => std::thread::sleep(frame_time), // nothing to interrupt it
+            }
+        }
+        self.screen.move_cursor(self.cursor.0, self.cursor.1);
+    }
 
-            // scroll print the word
-            self.scroll(&to_print, 15);
+    fn pause(&mut self, duration: Duration) {
+        // time to take in what's on screen before moving on (scaled by the animation speed). Any key cuts it short,
+        // including one that was pressed to skip an animation just before
+        if self.pending.pop_front().is_some() {
+            return;
+        }
+        let end = Instant::now() + self.motion.scale(duration);
+        loop {
+            let left = end.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return;
+            }
+            match self.keys.next_key(left.min(Duration::from_millis(50))) {
+                Ok(key) => {
+                    if let Some(recording) = &mut self.recording {
+                        recording.log(Action::Key(key));
+                    }
+                    return;
+                },
+                Err(RecvTimeoutError::Timeout) => self.tick(),
+                Err(RecvTimeoutError::Disconnected) => {
+                    std::thread::sleep(left);
+                    return;
+                },
+            }
         }
     }
 
     pub fn get_input(&mut self) -> String {
-        let (col, row) = self.coord; // to locate initial position. Shadowed later inside input loop
-        let row = row + 2;
-        let mut word = String::new(); // buffer for user entry
-
-        // move cursor to appropriate board row
-        write!(self.screen, "{}|   |   |   |   |   |{}{}",
-            cursor::Goto(col, row + self.turn as u16 * 2 - 1), // go to turn row, reprint blanks in case of failed guess
-            cursor::Goto(col + 2, row + self.turn as u16 * 2 - 1), // go to start of turn row's letters
-            cursor::Show
-        ).unwrap();
-        self.screen.flush().unwrap();
+        // typed is the buffer for user entry. It starts out empty on a new row, but a rejected guess stays in it to be fixed,
+        // with the cursor after its last letter
+        self.caret = self.typed.chars().count();
+
+        // go to turn row, reprint it in case of a failed guess (it's been shaken), and move cursor to where the next letter goes
+        self.draw_typed();
 
         // user inputs guess, letters will appear on the board
-        let input = stdin();
-        for key in input.keys() {
-            match key.unwrap() {
-                Key::Char('`') => {
-                    self.print_msg(&format!("\r{}", termion::clear::CurrentLine));
-                    self.print_msg("Exiting");
+        loop {
+            match self.next_key() {
+                key if self.bindings.command(key) == Some(Command::Quit) => {
+                    let saved = self.save_game(); // picked up again on the next launch
+                    self.clear_msg();
+                    self.print_msg(if saved { "Game saved" } else { "Exiting" });
+                    self.save_recording(history::now_secs());
                     std::thread::sleep(std::time::Duration::from_millis(555));
                     panic!("exiting program"); // for debugging
                 },
-                Key::Char('1') => { // enable hard mode
-                    self.print_msg(&format!("\r{}", termion::clear::CurrentLine));
-                    if self.hard { // if it's already enabled
-                        self.print_msg("Hard mode already enabled");
-                    } else if self.guesses.is_empty() { // enable only if you haven't guessed yet
-                        self.hard = true;
-                        self.print_msg("Hard mode enabled");
-                    } else {
-                        self.print_msg("Cannot enable hard mode"); // actual message is "Hard mode can only be enabled at the start of a round" but that's long and could make terminal panic  
-                    }
-                },
-                Key::Char('2') => {
-                    self.print_msg(&format!("\r{}", termion::clear::CurrentLine));
-                    if self.contrast {
-                        self.print_msg("High contrast mode already enabled");
-                    } else if self.guesses.is_empty() { // only if you haven't guessed yet (else you'd have to redraw coloured rows)
-                        self.contrast = true;
-                        self.print_msg("High contrast mode enabled");
-                    } else {
-                        self.print_msg("Cannot enable high contrast mode");
-                    }
-                },
+                // any time: whatever changes (e.g. the theme) gets drawn again when the menu closes
+                key if self.bindings.command(key) == Some(Command::Settings) => self.settings(),
+                key if self.bindings.command(key) == Some(Command::Help) => self.help(),
                 Key::Char('\n') => {
+                    if let Some(recording) = &mut self.recording {
+                        recording.log(Action::Submit(self.typed.to_uppercase()));
+                    }
                     break; // pressing enter breaks and returns the word String to main()
                 },
                 Key::Char(ch) => {
-                    if ch.is_alphabetic() && word.len() < 5 { // only enters up to 5 letters
-                        let (cursor_col, cursor_row) = self.screen.cursor_pos().unwrap();
-                        write!(self.screen, "{}{}",
-                            ch.to_uppercase(),
-                            cursor::Goto(cursor_col + 4, cursor_row)
-                        ).unwrap();
-                        word.push(ch);
-                    }
-                    if word.len() >= 5 {
-                        write!(self.screen, "{}",
-                            cursor::Hide
-                        ).unwrap();
+                    if ch.is_alphabetic() {
+                        self.edit_row(|letters, caret| {
+                            if *caret < letters.len() {
+                                letters[*caret] = ch; // typing over a letter replaces it
+                            } else if letters.len() < WORD_LENGTH { // only enters up to 5 letters
+                                letters.push(ch);
+                            } else {
+                                return;
+                            }
+                            *caret += 1; // next letter's spot
+                        });
                     }
-                    self.screen.flush().unwrap();
-                    self.print_msg(&format!("\r{}", termion::clear::CurrentLine)); // clear any errors displayed after first keypress
+                    self.clear_msg(); // clear any errors displayed after first keypress
                         // This gets called every time you press a key, which is unnecessary but works fine and not sure how else to do
                 },
-                Key::Backspace => {
-                    if !word.is_empty() {
-                        let (cursor_col, cursor_row) = self.screen.cursor_pos().unwrap();
-                        write!(self.screen, "{} {}", // moves back, overwrites with space, then moves back again
-                            cursor::Goto(cursor_col - 4, cursor_row),
-                            cursor::Goto(cursor_col - 4, cursor_row),
-                        ).unwrap();
-                        word.pop();
-                        if word.len() < 5 {
-                            write!(self.screen, "{}", cursor::Show).unwrap();
-                        }
-                        self.screen.flush().unwrap();
+                Key::Backspace => self.edit_row(|letters, caret| { // the letter before the cursor, the rest move back to fill the gap
+                    if *caret > 0 {
+                        *caret -= 1;
+                        letters.remove(*caret);
                     }
-                },
+                }),
+                Key::Delete => self.edit_row(|letters, caret| { // the letter under the cursor
+                    if *caret < letters.len() {
+                        letters.remove(*caret);
+                    }
+                }),
+                Key::Left => self.edit_row(|_, caret| *caret = caret.saturating_sub(1)),
+                Key::Right => self.edit_row(|_, caret| *caret += 1), // (no further than the end of what's typed)
+                Key::Home => self.edit_row(|_, caret| *caret = 0),
+                Key::End => self.edit_row(|letters, caret| *caret = letters.len()),
+                Key::Ctrl('u') => self.edit_row(|letters, caret| { // start the row again
+                    letters.clear();
+                    *caret = 0;
+                }),
                 _ => (),
             }
         }
-        word
+        self.typed.clone() // (kept until it's scored, in case it gets rejected)
     }
 
-    pub fn hard_check(&self, attempt: &Word) -> Result<(), String> {
-        // returns Ok if an attempted hard mode guess passes, Err (a message + char) if it violates the rules
+    fn edit_row(&mut self, edit: impl FnOnce(&mut Vec<char>, &mut usize)) {
+        // changes the letters typed so far and/or where the cursor is among them, then draws the row again
+        let mut letters: Vec<char> = self.typed.chars().collect();
+        edit(&mut letters, &mut self.caret);
+        self.caret = self.caret.min(letters.len());
+        let typed: String = letters.into_iter().collect();
+        if typed != self.typed {
+            self.flagged = None; // (they might have fixed it)
+        }
+        self.typed = typed;
+        self.draw_typed();
+    }
 
-        /*
-            HARD MODE RULES
-            Green reveals must be reused in the SAME SPOT
-            Yellow reveals must be reused in the word
-            In other words, correct positions must be reused exactly and overall letters revealed must be reused in the same or higher number
+    fn draw_typed(&mut self) {
+        // the row being typed, from the buffer, with the cursor on the tile the next letter goes in
+        // (hidden once it's past the end of a full row)
+        let lines = self.paint_row(&self.typed.to_uppercase(), &self.typed_faces());
+        self.print_row(self.turn, &lines);
+        self.cursor = self.geometry.tile(self.turn, self.caret);
+        self.screen.move_cursor(self.cursor.0, self.cursor.1);
+        self.screen.show_cursor(self.caret < WORD_LENGTH);
+        self.screen.flush();
+    }
 
-            Wordle will tell you (in this order) if:
-            1. you have a green reveal and you didn’t use it in the right spot or didn’t use it at all
-	            — “Xth letter must be L”
-            2. you have a yellow reveal that you didn’t use
-	            — “Guess must contain L”
-            In both cases it will only tell you the first error you made
-        */
+    fn typed_faces(&self) -> [Face; 5] {
+        let mut faces = [Face::Plain; 5];
+        if let Some(face) = self.flagged.and_then(|tile| faces.get_mut(tile)) {
+            *face = Face::Flagged;
+        }
+        faces
+    }
 
-        if self.turn <= 1 {
-            return Ok(()); // you don't need to check before turn 2 (also this function will panic on turn 1 without this)
-        }
-
-        // check for use of green matches:
-        // "for each letter of the previous guess, if that letter is in the same spot in the secret word (i.e. green match) it must also be used in that spot in the next attempt"
-        for (index, letter) in self.guesses.last().unwrap().contents().char_indices() {
-            if self.secret_word.contents().chars().nth(index).unwrap() == letter && attempt.contents().chars().nth(index).unwrap() != letter {
-                match index + 1 {
-                    1 => return Err(format!("1st letter must be {letter}")),
-                    2 => return Err(format!("2nd letter must be {letter}")),
-                    3 => return Err(format!("3rd letter must be {letter}")),
-                    4 => return Err(format!("4th letter must be {letter}")),
-                    5 => return Err(format!("5th letter must be {letter}")),
-                    _ => (),
-                }
+    fn settings(&mut self) {
+        // the settings menu, over whatever's on screen. Up and down pick a setting, left, right or Enter changes it
+        // (and saves it for the profile), Esc or any of the bound keys besides help closes it
+        self.overlay = Some(Overlay::Settings(0, None));
+        self.draw_overlay();
+        while let Some(Overlay::Settings(selected, _)) = self.overlay {
+            let settings = self.settings_shown();
+            self.overlay = Some(Overlay::Settings(selected, None));
+            match self.next_key() {
+                Key::Up => self.overlay = Some(Overlay::Settings((selected + settings.len() - 1) % settings.len(), None)),
+                Key::Down => self.overlay = Some(Overlay::Settings((selected + 1) % settings.len(), None)),
+                Key::Left | Key::Right | Key::Char('\n') | Key::Char(' ') => self.change_setting(settings[selected]),
+                Key::Esc => self.overlay = None,
+                key if self.bindings.command(key).is_some_and(|c| c != Command::Help) => self.overlay = None,
+                _ => continue,
+            }
+            if !self.paused {
+                self.repaint();
             }
         }
+    }
 
-        // check for yellow matches: "for each letter of the previous guess, ..."
-        for letter in self.guesses.last().unwrap().contents().chars() {
-            
-            // "... count how many of each letter in previous guess is ...""
-            let in_guess: usize = self.guesses.last().unwrap().contents().chars().filter(|c| *c == letter).count();
-            let in_secret: usize = self.secret_word.contents().chars().filter(|c| *c == letter).count();
-            let in_attempt: usize = attempt.contents().chars().filter(|c| *c == letter).count();
+    fn help(&mut self) {
+        // how to play and what the keys do, until any key is pressed
+        self.overlay = Some(Overlay::Help);
+        self.draw_overlay();
+        self.next_key();
+        self.overlay = None;
+        if !self.paused {
+            self.repaint();
+        }
+    }
 
-            // "for each letter in the previous guess, the attempt must contain at least as many of that letter as are in the last guess or in the secret word, whichever has fewer"
-            if in_attempt < std::cmp::min(in_guess, in_secret) {
-                return Err(format!("Guess must contain {letter}"));
-            }
+    fn settings_shown(&self) -> Vec<Setting> {
+        let mut settings = vec![Setting::Hard, Setting::Theme, Setting::Keyboard, Setting::Style, Setting::Animations];
+        // switching mid-game would muddle up whose game it is, and in a daily game it would skip
+        // main's check that the new profile hasn't already played today
+        if self.view == View::Welcome && !matches!(self.mode, Mode::Daily(_)) {
+            settings.push(Setting::Profile);
+        }
+        settings.push(Setting::Defaults); // (even in a replay, so the recorded key presses land on the same items)
+        settings
+    }
 
-            /*
-                To see why this works:
-                E R R O R -> last guess     M A R R Y (secret word)
-                Error would get two matches (Y & G), which means you need to have two in your next attempt
-                If the words were reversed it would still be true, Marry would have a green and a yellow and your next guess would have to include them
-                If you chose a word with different R positioning and all you got was yellows, it would still hold true.
-            */
+    fn setting_value(&self, setting: Setting) -> String {
+        match setting {
+            Setting::Hard if !self.guesses.is_empty() => format!("{} (locked)", if self.hard { "on" } else { "off" }),
+            Setting::Hard => String::from(if self.hard { "on" } else { "off" }),
+            Setting::Theme => self.theme.name.clone(),
+            Setting::Keyboard => self.layout.name().to_uppercase(),
+            Setting::Style => self.style.name().to_string(),
+            Setting::Animations => self.motion.name(),
+            Setting::Profile => self.profile.name().to_string(),
+            Setting::Defaults => String::new(),
+        }
+    }
 
-            /*
-                there is an ever so slight bug where if you've revealed the first letter as green, but there's another
-                of that letter revealed as yellow later in the word, a different yellow letter before it will not get
-                "error priority". e.g. "T R E A T", where the letter 1 is green and 4/5 are yellow, if you type "TRAPS",
-                the message will be "Guess must contain T". Which I think is fine, because the end result is the same
-                I'm pretty sure real Wordle displays the "first" error in the word, but this is an extremely fringe case
-                with no real effect on the outcome
-            */
+    fn change_setting(&mut self, setting: Setting) {
+        // on to the next option, wrapping round
+        match setting {
+            Setting::Hard if !self.guesses.is_empty() => return, // hard mode can only be changed before the first guess
+            Setting::Hard => self.hard = !self.hard,
+            Setting::Theme => self.theme = self.themes.next(&self.theme.name),
+            Setting::Keyboard => self.layout = self.layout.next(),
+            Setting::Style => {
+                self.set_style(self.style.next());
+                self.place_cursor(); // the tiles might have changed size
+            },
+            Setting::Animations => self.motion = self.motion.next(),
+            Setting::Profile => { // next profile along, wrapping back round to the default one
+                let profiles = profile::list();
+                let next = match profiles.iter().position(|p| *p == self.profile) {
+                    Some(index) => profiles[(index + 1) % profiles.len()].clone(),
+                    None => Profile::default(),
+                };
+                self.set_profile(next);
+                return; // nothing to save, the new profile's own preferences have just been loaded
+            },
+            Setting::Defaults if self.replay.is_some() => return, // a replay shouldn't change anyone's config
+            Setting::Defaults => {
+                let saved = config::save_defaults(&self.prefs());
+                if saved.is_ok() { // they're what a new profile would get from now on
+                    self.defaults = Prefs { theme: None, ..self.prefs() };
+                    self.themes.default = self.theme.name.clone();
+                }
+                let note = if saved.is_ok() { "Saved to config file" } else { "Could not save config" };
+                if let Some(Overlay::Settings(selected, _)) = self.overlay {
+                    self.overlay = Some(Overlay::Settings(selected, Some(note)));
+                }
+                return; // (the profile's own settings are already saved)
+            },
         }
+        self.save_prefs();
+    }
 
-        Ok(())
+    fn draw_overlay(&mut self) {
+        // a box in the middle of the screen, over everything else, with lines of text in it (and whether each is highlighted)
+        let lines: Vec<(String, bool)> = match self.overlay {
+            Some(Overlay::Settings(selected, note)) => {
+                let settings = self.settings_shown().into_iter().enumerate();
+                let settings = settings.map(|(index, setting)| (format!("{:<10} {}", setting.name(), self.setting_value(setting)).trim_end().to_string(), index == selected));
+                let done = self.bindings.keys(Command::Settings).first().map(input::label).unwrap_or_default();
+                let hint = note.map(String::from).unwrap_or(format!("←→ change, {done} done"));
+                std::iter::once(String::from("Settings")).map(|l| (l, false))
+                    .chain(settings)
+                    .chain([(hint, false)])
+                    .collect()
+            },
+            Some(Overlay::Help) => self.help_lines().into_iter().map(|line| (line, false)).collect(),
+            None => return,
+        };
+        let width = lines.iter().map(|(line, _)| line.chars().count()).max().unwrap_or(0) as u16 + 4;
+        let area = self.geometry.overlay(width, lines.len() as u16 + 2);
+        let inside = area.width.saturating_sub(4) as usize; // (a space either side of the text)
+        let (top, bottom, side, across) = match self.style {
+            BoardStyle::Ascii => (['+', '+'], ['+', '+'], '|', "-"),
+            BoardStyle::Tiles => (['┌', '┐'], ['└', '┘'], '│', "─"),
+        };
+        let across = across.repeat(area.width.saturating_sub(2) as usize);
+        self.screen.print(area.col, area.row, &format!("{}{across}{}", top[0], top[1]));
+        for (index, (line, highlight)) in lines.iter().enumerate() {
+            let text: String = line.chars().take(inside).collect();
+            let style = if *highlight { Style::reversed() } else { Style::default() };
+            let spans = [Span::plain(side), Span::styled(format!(" {text:<inside$} "), style), Span::plain(side)];
+            self.screen.print_spans(area.col, area.row + 1 + index as u16, &spans);
+        }
+        self.screen.print(area.col, area.row + area.height - 1, &format!("{}{across}{}", bottom[0], bottom[1]));
+        self.screen.show_cursor(false);
+        self.screen.flush();
     }
 
-    pub fn scroll(&mut self, print: &str, duration: u64) {
-        for item in print.chars() {
-            write!(self.screen, "{item}").unwrap();
-            self.screen.flush().unwrap();
-            std::thread::sleep(Duration::from_millis(duration));
+    fn help_lines(&self) -> Vec<String> {
+        // the keys (the same whatever the bindings), and how to play when there's room
+        let keys = [ // what each does, with a shorter version for compact
+            (String::from("Enter"), "submit a guess", "submit"),
+            (String::from("←→ Home End"), "move", "move"),
+            (String::from("Bksp Del"), "erase a letter", "erase"),
+            (String::from("Ctrl-U"), "clear the row", "clear row"),
+            (self.bindings.label(Command::Settings), "settings", "settings"),
+            (self.bindings.label(Command::Help), "help", "help"),
+            (self.bindings.label(Command::Quit), "quit (and save)", "quit"),
+        ];
+        let key_width = keys.iter().map(|(key, _, _)| key.chars().count()).max().unwrap_or(0);
+        let compact = self.geometry.compact;
+        let keys = keys.iter().map(|(key, what, short)| format!("{key:<key_width$} {}", if compact { short } else { what }));
+        if compact {
+            return std::iter::once(String::from("Keys (any key closes)")).chain(keys).collect();
         }
+        let how_to = format!("HOW TO PLAY\n\nGuess the Wordle in {GUESSES} tries\nEach guess must be a valid {WORD_LENGTH}-letter word\nThe colour of the tiles will change\nto show how close your guess was\n");
+        how_to.split('\n').map(String::from).chain(keys).chain([String::new(), String::from("Any key to close")]).collect()
+    }
+
+    pub fn hard_check(&self, attempt: &Word) -> Result<(), GuessError> {
+        hard_check(&self.secret_word, self.guesses.last(), attempt)
+    }
+
+    pub fn scroll(&mut self, col: u16, row: u16, print: &str, duration: u64) {
+        // prints one character at a time from col, row, duration ms apart
+        let chars: Vec<char> = print.chars().collect();
+        self.animate(chars.len(), Duration::from_millis(duration), |board, frame| {
+            board.screen.print(col, row, &chars[..=frame].iter().collect::<String>());
+        });
     }
 
     pub fn win_message(&mut self) {
@@ -502,262 +1169,466 @@ impl Board {
         } else {
             message = format!("Failure: {}", self.secret_word.contents());
         }
+        if let Some(race) = &mut self.race {
+            race.send_done(self.win);
+        }
 
         // print win message under the grid, above the keyboard (same row as error messages)
-        let (col, row) = self.coord;
-        let message_row = row + 16;
-        write!(self.screen, "{}{}",
-            cursor::Hide,
-            cursor::Goto(col + 10 - (message.len() as u16 / 2), message_row)
-        ).unwrap();
-        self.screen.flush().unwrap();
-        self.scroll(&message, 70);
-        std::thread::sleep(Duration::from_secs(2)); // wait a couple seconds
+        self.view = View::GameOver;
+        self.message = message.clone();
+        let col = self.geometry.board().centred(&message);
+        let message_row = self.geometry.message_row();
+        self.screen.show_cursor(false);
+        self.scroll(col, message_row, &message, 70);
+
+        // daily mode: when the next puzzle unlocks (between the keyboard and "press any key")
+        self.draw_countdown();
+        self.pause(Duration::from_secs(2)); // wait a couple seconds
 
         // "press any key to continue"
-        let exit_message = "Press any key to continue";
-        let press_message_row = row + 22;
-        write!(self.screen,
-            "{}{}",
-            cursor::Goto(col + 10 - (exit_message.len() as u16 / 2), press_message_row), // this ensures the text is centred
-            exit_message
-        ).unwrap();
-        self.screen.flush().unwrap();
+        self.draw_continue_prompt();
 
         // wait for key press
-        press_to_continue();
+        self.wait_for_key();
+    }
+
+    fn draw_game_over(&mut self) {
+        self.draw_countdown();
+        self.draw_continue_prompt();
+    }
+
+    fn draw_countdown(&mut self) {
+        if let Mode::Daily(_) = self.mode {
+            let label = if self.geometry.compact { "Next in" } else { "Next Wordle in" };
+            let countdown = format!("{label} {}", daily::format_countdown(daily::until_tomorrow(&self.timezone)));
+            self.screen.print(self.geometry.board().centred(&countdown), self.geometry.footer_row(), &countdown);
+            self.screen.flush();
+        }
+    }
+
+    fn draw_continue_prompt(&mut self) {
+        let exit_message = if self.geometry.compact { "Press any key" } else { "Press any key to continue" };
+        let col = self.geometry.board().centred(exit_message); // this ensures the text is centred
+        self.screen.print(col, self.geometry.prompt_row(), exit_message);
+        self.screen.flush();
     }
 
     pub fn print_msg(&mut self, msg: &str) { // print errors centred under the board but restores cursor after
-        let (col, row) = self.coord;
-        let message_row = row + 16;
-        let (return_col, return_row) = self.screen.cursor_pos().unwrap(); // cursor position before jumping
-        write!(self.screen, "{}{}{}",
-            cursor::Goto(col + 10 - (msg.len() as u16 / 2), message_row),
-            msg,
-            cursor::Goto(return_col, return_row),
-            // note that zsh doesn't like cursor Save/Hide so needed to use Goto()
-        ).unwrap();
-        self.screen.flush().unwrap();
-    }
-
-    pub fn print_welcome_msg(&mut self, msg: &str) { // version for the welcome screen
-        let (col, row) = self.coord;
-        let message_row = row + 11;
-        let (return_col, return_row) = self.screen.cursor_pos().unwrap(); // cursor position before jumping
-        write!(self.screen, "{}{}{}",
-            cursor::Goto(col + 10 - (msg.len() as u16 / 2), message_row),
-            msg,
-            cursor::Goto(return_col, return_row),
-        ).unwrap();
-        self.screen.flush().unwrap();
+        self.log_msg(msg);
+        self.show_msg(msg);
+    }
+
+    fn show_msg(&mut self, msg: &str) { // print_msg without recording it, for repainting
+        self.message = msg.to_string();
+        let msg = if self.geometry.compact { shorten(msg) } else { msg };
+        self.screen.print(self.geometry.board().centred(msg), self.geometry.message_row(), msg);
+        self.screen.move_cursor(self.cursor.0, self.cursor.1); // back to where the cursor was before jumping
+            // note that zsh doesn't like cursor Save/Hide so it's moved back by hand
+        self.screen.flush();
+    }
+
+    fn clear_msg(&mut self) {
+        self.message.clear();
+        self.screen.clear_line(self.geometry.message_row());
+        self.screen.move_cursor(self.cursor.0, self.cursor.1);
+        self.screen.flush();
+    }
+
+    pub fn print_welcome_msg(&mut self, msg: &str) { // version for the welcome screen (same spot these days)
+        self.print_msg(msg);
+    }
+
+    fn log_msg(&mut self, msg: &str) {
+        if let Some(recording) = &mut self.recording {
+            if !msg.is_empty() {
+                recording.log(Action::Message(msg.to_string()));
+            }
+        }
     }
 
     pub fn stats(&mut self) {
         /*
-            the stats vector indices represent:
-            0: 1s
-            1: 2s
-            2: 3s
-            3: 4s
-            4: 5s
-            5: 6s
-            6: failures
-            7: current streak
-            8: max streak
-
-                1           100         1           1
-                Played      Win %       Current     Max
-                                        Streak      Streak
+            stats are kept in stats.rs as wins per turn (1s to 6s), failures, current streak and max streak
+            there's a separate set for every mode (normal, hard, ...), shown as tabs along the top
+            the per-game history (history.rs) adds openers, failed words and the trend line
+            the screen looks like:
+
+                       < Normal    Normal* >
+              12        83        3.9       3         5
+              Played    Win %     Avg       Current   Max
+                                            Streak    Streak
             | 1 | 0
             | 2 | 0
-            | 3 ||||||||||||||||||||||||||||||| 15              -> this is 30 ticks (n/i first one)
-            | 4 ||||||||||||||||||||||||||||||||||||||||| 20    -> this is 40 ticks
+            | 3 ||||||||||||||||||||||||||||||| 15              -> bars scale to the screen width
+            | 4 ||||||||||||||||||||||||||||||||||||||||| 20
             | 5 ||||||||||| 5
             | 6 | 1
+            | X || 2                                            -> failures
 
-            stats graph is 48 across
+            Trend    ▃▄▄▆▅▇█
+            Openers  CRANE 4 (75%)  SLATE 2 (50%)
+            Hardest  JAZZY, FUZZY
         */
 
-        let filename = "./wordle_stats.txt";
-        let mut stats: Vec<u16> = Vec::new(); // to hold nine numbers representing stats
-        if let Ok(lines) = read_file_lines(filename) { // will attempt to read a file but do nothing if the file does not exist
-            for line in lines {
-                if let Ok(value) = line {
-                    if let Ok(number) = value.parse::<u16>() {
-                        stats.push(number); // push the lines onto the vector as long as each one is a number
-                        if stats.len() >= 9 { // and only until there are nine
-                            break;
-                        }
-                    }
+        self.race_drawn = None; // the opponent panel doesn't belong on the stats screen
+
+        // record this game under its mode and save (see stats.rs), then display whatever got saved
+        let key = self.stats_key();
+        let game = history::GameRecord::new(
+            key.clone(),
+            self.secret_word.contents().clone(),
+            self.guesses.iter().map(|g| g.contents().clone()).collect(),
+            self.win,
+        );
+        let day = match self.mode {
+            Mode::Daily(day) => Some(day),
+            _ => None,
+        };
+        let record = match self.replay {
+            Some(_) => None, // the recorded game already counted, just show the stats as they are now
+            None => Some(stats::record(&self.profile, &game, day)),
+        };
+        let mut history_error = None;
+        let (mut book, saved) = match record {
+            None => {
+                // the watcher's stats, which needn't have this mode (or anything) in them. It still gets a tab, of zeros
+                let mut book = stats::load(&self.profile).unwrap_or_default();
+                book.get_mut(&key);
+                (book, None)
+            },
+            Some(Ok(saved)) => {
+                history_error = saved.history_error.map(|e| e.to_string());
+                (saved.book, Some(Ok(saved.backup)))
+            },
+            Some(Err(e)) => { // couldn't save, but still show stats as they would have been
+                let mut book = stats::load(&self.profile).unwrap_or_default();
+                match day {
+                    Some(day) => book.get_mut(&key).record_daily(self.win, self.turn, day),
+                    None => book.get_mut(&key).record(self.win, self.turn),
                 }
-            }
-        }
+                (book, Some(Err(e)))
+            },
+        };
+        self.save_recording(game.timestamp);
+        book.expire_streaks(daily::today(&self.timezone)); // other daily tabs might have missed days
+        let records = history::load(&self.profile).unwrap_or_default(); // the extra detail is optional, the screen works without it
+
+        // start on this game's tab. Left/Right switch between modes, anything else exits
+        let tabs = book.keys();
+        let selected = tabs.iter().position(|k| *k == key).unwrap_or(0);
+        let mut stats_screen = StatsScreen { book, records, tabs, selected, saved, history_error, prompt: false };
+        self.view = View::Stats;
+        self.draw_stats(&stats_screen);
+        self.pause(Duration::from_secs(2)); // wait a couple seconds
 
-        // check the vector is valid, and init to nine zeros if it is not
-        if stats.len() != 9 {
-            stats.clear();
-            for _ in 1..=9 {
-                stats.push(0);
+        // "press any key to exit"
+        stats_screen.prompt = true;
+        self.draw_exit_prompt(&stats_screen);
+        self.stats_screen = Some(stats_screen);
+
+        loop {
+            let key = self.next_key();
+            let Some(mut stats_screen) = self.stats_screen.take() else { break };
+            match key {
+                Key::Left if stats_screen.selected > 0 => stats_screen.selected -= 1,
+                Key::Right if stats_screen.selected + 1 < stats_screen.tabs.len() => stats_screen.selected += 1,
+                Key::Left | Key::Right => { // already at the end, nothing to redraw
+                    self.stats_screen = Some(stats_screen);
+                    continue;
+                },
+                _ => break,
             }
+            self.draw_stats(&stats_screen);
+            self.draw_exit_prompt(&stats_screen);
+            self.stats_screen = Some(stats_screen);
         }
+    }
 
-        // update stats
-        if self.win { // if you won
-            if let Some(count) = stats.get_mut(self.turn - 1) {
-                *count += 1; // increase wins associated with turn number
-            }
-            if let Some(n) = stats.get_mut(7) {
-                *n += 1; // streak +1
+    fn draw_stats(&mut self, stats_screen: &StatsScreen) {
+        if self.geometry.compact {
+            self.draw_compact_stats(stats_screen);
+            return;
+        }
+        let StatsScreen { book, records, tabs, selected, saved, history_error, .. } = stats_screen;
+        let selected = *selected;
+        let area = self.geometry.stats();
+        let (col, row, width) = (area.col, area.row, area.width);
+        let key = &tabs[selected];
+        let stats = book.get(key);
+        let this_game = *key == self.stats_key(); // only highlight this game's bar on its own tab
+
+        // mode tabs, selected one underlined. Hard mode tabs get an asterisk like NYT
+        // with a lot of modes they won't all fit, so show as many as will either side of the selected one
+        let labels: Vec<String> = tabs.iter().map(|tab| stats::label(tab)).collect();
+        let room = (width as usize).saturating_sub(2); // (the arrows)
+        let (mut first, mut last) = (selected, selected);
+        let mut tab_len = labels[selected].len() + 4;
+        loop {
+            let next = last + 1 < labels.len() && tab_len + labels[last + 1].len() + 4 <= room;
+            if next {
+                last += 1;
+                tab_len += labels[last].len() + 4;
             }
-        } else { // if you failed
-            if let Some(count) = stats.get_mut(6) {
-                *count += 1; // failure count
+            let previous = first > 0 && tab_len + labels[first - 1].len() + 4 <= room;
+            if previous {
+                first -= 1;
+                tab_len += labels[first].len() + 4;
             }
-            if let Some(n) = stats.get_mut(7) {
-                *n = 0; // reset streak
+            if !next && !previous {
+                break;
             }
         }
-        let streak: u16 = *stats.get(7).unwrap(); // note that streak/max are copies of the Vec data, not references, hence re-binding them later
-        let max: u16 = *stats.get(8).unwrap();
-        if streak > max {
-            if let Some(n) = stats.get_mut(8) {
-                *n = streak; // update max streak (in the stats vector) if current streak exceeds
-            }
+        let mut tab_bar = vec![Span::plain(if selected > 0 { "<" } else { " " })];
+        for (index, label) in labels.iter().enumerate().take(last + 1).skip(first) {
+            let style = if index == selected { Style::underlined() } else { Style::default() };
+            tab_bar.extend([Span::plain("  "), Span::styled(label.as_str(), style), Span::plain("  ")]);
+        }
+        tab_bar.push(Span::plain(if selected + 1 < tabs.len() { ">" } else { " " }));
+        self.screen.clear(); // wipe the screen
+        self.screen.show_cursor(false);
+        self.screen.print_spans(area.centre().saturating_sub(tab_len as u16 / 2 + 1).max(1), self.geometry.stats_tabs_row(), &tab_bar);
+
+        // display the stats: played, win%, average guesses, current streak, max streak
+        let average = match stats.average_guesses() {
+            Some(average) => format!("{average:.1}"),
+            None => String::from("-"), // no wins to average
+        };
+        let numbers = [stats.played().to_string(), stats.win_percentage().to_string(), average, stats.streak.to_string(), stats.max_streak.to_string()];
+        let labels = [("Played", ""), ("Win %", ""), ("Avg", ""), ("Current", "Streak"), ("Max", "Streak")];
+        for (index, (number, (label, second_line))) in numbers.iter().zip(labels).enumerate() {
+            let stats_col = self.geometry.stats_number(index, numbers.len());
+            self.screen.print(stats_col, row, number);
+            self.screen.print(stats_col, row + 1, label); // jump down a line
+            self.screen.print(stats_col, row + 2, second_line);
         }
 
-        // calculate board position (top left coordinate)
-        let (mut col, mut row) = termion::terminal_size().unwrap();
-        match (col / 2).checked_sub(24) { // checked subtraction ensures no possible overflow error (and crash) if terminal is small
-            Some(n) => col = n,
-            None => col = 0,
-        }
-        match (row / 2).checked_sub(5) {
-            Some(n) => row = n,
-            None => row = 0,
-        }
-
-        // calculate some stats
-        let played: u16 = stats[..=6].iter().fold(0, |acc, x| acc + x);
-        let won: u16 = stats[..=5].iter().fold(0, |acc, x| acc + x);
-        let percentage: u16 = ((won as f64 / played as f64) * 100.0) as u16;
-        let streak: u16 = *stats.get(7).unwrap(); // redundant shadowing? But "streak" is a copy of vector data and could have been updated, so re-bind
-        let max: u16 = *stats.get(8).unwrap(); // possibly redundant shadowing but just in case
-
-        // display the stats: played, win%, current streak, max streak
-        let stats_col = col + 4;
-        write!(self.screen, "{}{}{}{played}{}{percentage}{}{streak}{}{max}{}Played{}Win %{}Current{}Max{}Streak{}Streak",
-            clear::All, // wipe the screen
-            cursor::Hide, // hide cursor
-            cursor::Goto(stats_col, row),
-            cursor::Goto(stats_col + 12, row),
-            cursor::Goto(stats_col + 24, row),
-            cursor::Goto(stats_col + 36, row),
-            cursor::Goto(stats_col, row + 1), // jump down a line
-            cursor::Goto(stats_col + 12, row + 1),
-            cursor::Goto(stats_col + 24, row + 1),
-            cursor::Goto(stats_col + 36, row + 1),
-            cursor::Goto(stats_col + 24, row + 2), // jump down and back
-            cursor::Goto(stats_col + 36, row + 2),
-        ).unwrap();
-
-        // display the graph
-        let graph_row = row + 4; // dropping down to graph level
-        write!(self.screen, "{}| 1 |{}| 2 |{}| 3 |{}| 4 |{}| 5 |{}| 6 |",
-            cursor::Goto(col, graph_row),
-            cursor::Goto(col, graph_row + 1),
-            cursor::Goto(col, graph_row + 2),
-            cursor::Goto(col, graph_row + 3),
-            cursor::Goto(col, graph_row + 4),
-            cursor::Goto(col, graph_row + 5),
-        ).unwrap();
+        // display the graph: a row for each number of guesses, then failures
+        let graph_row = self.geometry.stats_graph_row(); // dropping down to graph level
+        let names = ["1", "2", "3", "4", "5", "6", "X"];
+        let counts = [stats.wins[0], stats.wins[1], stats.wins[2], stats.wins[3], stats.wins[4], stats.wins[5], stats.failures];
 
         // which is the "mode guess"? (it will take up the graph width and the others will be relative)
-        let big_bar: u16 = stats[..=5].iter().fold(0, |acc, x| acc.max(*x));
+        let big_bar: u16 = counts.iter().fold(0, |acc, x| acc.max(*x));
+        let max_ticks = width.saturating_sub(12) as f64; // leave room for the "| 1 |" label and the count
 
         // print the bars
-        let bar_col = col + 5;
-        for line in 0..=5 {
-            let count = *stats.get(line).unwrap(); // how many times have you won off that number of guesses
-            let ticks: u16 = ((count as f64 / big_bar as f64) * 40.0) as u16; // number representing the length of each bar
-            let mut bar = String::new(); // the actual bar characters to print
-            for _ in 1..=ticks {
-                bar.push('|');
-            }
-            if line + 1 == self.turn && self.win { // print the "turn row" green, unless failed
-                if self.contrast {
-                    write!(self.screen, "{}{}{bar} {count}{}",
-                        cursor::Goto(bar_col, graph_row + line as u16),
-                        color::Fg(color::LightMagenta),
-                        color::Fg(color::Reset)
-                    ).unwrap();
-                } else {
-                    write!(self.screen, "{}{}{bar} {count}{}",
-                        cursor::Goto(bar_col, graph_row + line as u16),
-                        color::Fg(color::LightGreen),
-                        color::Fg(color::Reset)
-                    ).unwrap();
-                }
+        let bar_col = self.geometry.stats_bar_col();
+        for (line, (name, count)) in names.iter().zip(counts).enumerate() {
+            let ticks = if big_bar == 0 { 0 } else { ((count as f64 / big_bar as f64) * max_ticks) as usize }; // number representing the length of each bar
+            let bar = "|".repeat(ticks); // the actual bar characters to print
+            self.screen.print(col, graph_row + line as u16, &format!("| {name} |"));
+            if line + 1 == self.turn && self.win && this_game { // print the "turn row" green, unless failed
+                self.screen.print_spans(bar_col, graph_row + line as u16, &[self.theme.paint(&format!("{bar} {count}"), Letter::Green, self.colours)]);
             } else {
-                write!(self.screen, "{}{bar} {count}",
-                    cursor::Goto(bar_col, graph_row + line as u16)
-                ).unwrap();
+                self.screen.print(bar_col, graph_row + line as u16, &format!("{bar} {count}"));
             }
         }
 
-        // flush the output stream
-        self.screen.flush().unwrap();
-
-        // attempt to write the stats to file
-        let save_message_row = row + 11;
-        let file = OpenOptions::new().write(true).create(true).open(filename);
-        match file {
-            Ok(mut file_out) => {
-                for items in stats { // we're ignoring errors but notifying the user as long as it's successful
-                    if let Ok(_)= write!(file_out, "{items}\n") { // write the stats to the file buffer
-                        if let Ok(_) = file_out.flush() { // flush the file output and print message if successful
-                            let saved_message = "Stats saved";
-                            write!(self.screen, "{}{}",
-                                cursor::Goto(col + 23 - (saved_message.len() as u16 / 2), save_message_row),
-                                saved_message
-                            ).unwrap();
-                            self.screen.flush().unwrap();
-                        }
-                    }
+        // history-based extras, each line cut to fit the stats width
+        let insights = history::insights(records, key, width.saturating_sub(11) as usize);
+        let detail_row = self.geometry.stats_details_row();
+        let details = [
+            ("Trend", history::sparkline(&insights.trend)),
+            ("Openers", stats::format_openers(&insights.openers)),
+            ("Hardest", insights.hardest.join(", ")),
+        ];
+        for (line, (label, detail)) in details.iter().enumerate() {
+            if detail.is_empty() {
+                continue; // nothing in the history for this mode yet
+            }
+            let detail: String = detail.chars().take(width.saturating_sub(11) as usize).collect();
+            self.screen.print(col, detail_row + line as u16, label);
+            self.screen.print(self.geometry.stats_detail_col(), detail_row + line as u16, &detail);
+        }
+
+        // let the user know how saving went
+        let save_message_row = self.geometry.stats_saved_row();
+        match saved {
+            None => {
+                let replay_message = "Replay, stats not saved";
+                self.screen.print(area.centred(replay_message), save_message_row, replay_message);
+            },
+            Some(Ok(backup)) => {
+                let saved_message = match history_error {
+                    Some(e) => format!("Stats saved, but not the game history: {e}"),
+                    None => String::from("Stats saved"),
+                };
+                self.screen.print(area.centred(&saved_message), save_message_row, &saved_message);
+                if backup.is_some() { // the old file was unreadable, it's been moved aside (stats restart from zero)
+                    let backup_message = "Stats file was corrupted and has been backed up";
+                    self.screen.print(area.centred(backup_message), save_message_row + 1, backup_message);
                 }
             },
-            Err(e) => {
-                let error_message = "Could not save stats:";
-                write!(self.screen, "{}{}{}{e}", // notifying if there's a problem creating/opening the file
-                    cursor::Goto(col + 23 - (error_message.len() as u16 / 2), save_message_row + 2),
-                    error_message, // this will print the error *below* "press any key" line
-                    cursor::Goto(col + 23 - (e.to_string().len() as u16 / 2), save_message_row + 3)
-                    // the above "to_string()" to get to use len() should work but I'm not sure, it's hard to test
-                ).unwrap();
-                self.screen.flush().unwrap();
+            Some(Err(e)) => {
+                let error_message = format!("Could not save stats: {e}");
+                // notifying if there's a problem creating/opening the file
+                self.screen.print(area.centred(&error_message), save_message_row, &error_message);
             },
         }
-        std::thread::sleep(Duration::from_secs(2)); // wait a couple seconds
 
-        // "press any key to exit"
-        let exit_message = "Press any key to exit";
-        let press_message_row = row + 12;
-        write!(self.screen,
-            "{}{}",
-            cursor::Goto(col + 23 - (exit_message.len() as u16 / 2), press_message_row),
-            exit_message
-        ).unwrap();
-        self.screen.flush().unwrap();
+        // flush the output stream
+        self.screen.flush();
+    }
 
-        // wait for key press
-        press_to_continue();
+    fn draw_compact_stats(&mut self, stats_screen: &StatsScreen) {
+        // the numbers and bars only, in the same space as the compact board:
+        //   < Normal* >
+        //   Played 12 Win 83% Avg 3.9
+        //   Streak 3 (max 5)
+        //   1 | 0
+        //   ...
+        let area = self.geometry.stats();
+        let (col, row) = (area.col, area.row);
+        let key = &stats_screen.tabs[stats_screen.selected];
+        let stats = stats_screen.book.get(key);
+        let this_game = *key == self.stats_key();
+
+        let left = if stats_screen.selected > 0 { "< " } else { "  " };
+        let right = if stats_screen.selected + 1 < stats_screen.tabs.len() { " >" } else { "  " };
+        let tab = format!("{left}{}{right}", stats::label(key));
+        let average = stats.average_guesses().map(|a| format!("{a:.1}")).unwrap_or(String::from("-"));
+        self.screen.clear();
+        self.screen.show_cursor(false);
+        self.screen.print(area.centred(&tab), row, &tab);
+        self.screen.print(col, row + 1, &format!("Played {} Win {}% Avg {average}", stats.played(), stats.win_percentage()));
+        self.screen.print(col, row + 2, &format!("Streak {} (max {})", stats.streak, stats.max_streak));
+
+        let counts = [stats.wins[0], stats.wins[1], stats.wins[2], stats.wins[3], stats.wins[4], stats.wins[5], stats.failures];
+        let big_bar = counts.iter().copied().max().unwrap_or(0);
+        for (line, (name, count)) in ["1", "2", "3", "4", "5", "6", "X"].iter().zip(counts).enumerate() {
+            let ticks = if big_bar == 0 { 0 } else { count as usize * 18 / big_bar as usize };
+            let bar = format!("{} {count}", "|".repeat(ticks));
+            let bar = match line + 1 == self.turn && self.win && this_game {
+                true => self.theme.paint(&bar, Letter::Green, self.colours),
+                false => Span::plain(bar),
+            };
+            self.screen.print_spans(col, self.geometry.stats_graph_row() + line as u16, &[Span::plain(format!("{name} |")), bar]);
+        }
+        self.screen.flush();
+    }
+
+    fn draw_exit_prompt(&mut self, stats_screen: &StatsScreen) {
+        let several_tabs = stats_screen.tabs.len() > 1;
+        if self.geometry.compact { // how saving went, then the prompt, on the line under the bars
+            let saved = match stats_screen.saved {
+                Some(Ok(_)) if stats_screen.history_error.is_some() => "Part saved",
+                Some(Ok(_)) => "Saved",
+                Some(Err(_)) => "Not saved",
+                None => "Replay",
+            };
+            let exit_message = format!("{saved}. {}", if several_tabs { "Left/Right, any key" } else { "Any key to exit" });
+            self.screen.print(self.geometry.stats().centred(&exit_message), self.geometry.stats_prompt_row(), &exit_message);
+            self.screen.flush();
+            return;
+        }
+        let exit_message = if several_tabs { "Left/Right for other modes, any key to exit" } else { "Press any key to exit" };
+        self.screen.print(self.geometry.stats().centred(exit_message), self.geometry.stats_prompt_row(), exit_message);
+        self.screen.flush();
+    }
+}
+
+fn score(secret: &Word, guess: &Word) -> [Letter; 5] {
+    // the colour of each letter of a guess. A free function so games without a Board (see plain.rs) score the same way
+    let mut match_counter: HashMap<char, usize> = HashMap::new();
+    let mut letter_colours: [Letter; 5] = [Letter::Grey; 5];
+    let mut secret_word: [char; 5] = ['_'; 5];
+    let mut guess_word: [char; 5] = ['_'; 5];
+    for (index, guess_letter) in guess.contents().char_indices() {
+        guess_word[index] = guess_letter;
+    }
+
+    // check GREEN matches (same-index matches)
+    for (index, secret_letter) in secret.contents().char_indices() {
+        secret_word[index] = secret_letter;
+        if secret_letter == guess_word[index] {
+            letter_colours[index] = Letter::Green;
+            match_counter.entry(secret_letter).and_modify(|count| *count += 1).or_insert(1);
+        }
+    }
+    
+    // check YELLOW matches (a secret word's letter exists in guess word and is still GREY)
+    // AND the amount of that letter in the secret word is MORE than the number that have been logged in the map already
+    for secret_letter in secret.contents().chars() {
+        for (index, guess_letter) in guess.contents().char_indices() {
+            if guess_letter == secret_letter
+            && secret.contents().chars().filter(|s| s == &secret_letter).count() > *match_counter.get(&secret_letter).unwrap_or(&0) {
+                if let Letter::Grey = letter_colours[index] {
+                    letter_colours[index] = Letter::Yellow;
+                    match_counter.entry(secret_letter).and_modify(|count| *count += 1).or_insert(1);
+                }
+            }
+        }
     }
+    letter_colours
 }
 
-fn read_file_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>> // this is from Rust By Example for "reading lines"
-where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines()) //returns an iterator to the reader of the lines of the file
+pub fn pattern(secret: &Word, guess: &Word) -> String {
+    // a guess's colours as G (green), Y (yellow) and . (grey), e.g. "G.Y.."
+    score(secret, guess).iter().map(|l| l.pattern_char()).collect()
+}
+
+pub fn hard_check(secret: &Word, last_guess: Option<&Word>, attempt: &Word) -> Result<(), GuessError> {
+    // returns Ok if an attempted hard mode guess passes, Err (the letter, and where it had to go) if it violates the rules
+    // only the last guess matters: it had to follow the rules too, so it carries everything revealed before it
+
+    /*
+        HARD MODE RULES
+        Green reveals must be reused in the SAME SPOT
+        Yellow reveals must be reused in the word
+        In other words, correct positions must be reused exactly and overall letters revealed must be reused in the same or higher number
+
+        Wordle will tell you (in this order) if:
+        1. you have a green reveal and you didn’t use it in the right spot or didn’t use it at all
+	            — “Xth letter must be L”
+        2. you have a yellow reveal that you didn’t use
+	            — “Guess must contain L”
+        In both cases it will only tell you the first error you made
+    */
+
+    let Some(last_guess) = last_guess else {
+        return Ok(()); // nothing revealed yet on turn 1
+    };
+
+    // check for use of green matches:
+    // "for each letter of the previous guess, if that letter is in the same spot in the secret word (i.e. green match) it must also be used in that spot in the next attempt"
+    for (index, letter) in last_guess.contents().char_indices() {
+        if secret.contents().chars().nth(index).unwrap() == letter && attempt.contents().chars().nth(index).unwrap() != letter {
+            return Err(GuessError::HardModeMissingLetter { letter, position: Some(index) });
+        }
+    }
+
+    // check for yellow matches: "for each letter of the previous guess, ..."
+    for letter in last_guess.contents().chars() {
+        
+        // "... count how many of each letter in previous guess is ...""
+        let in_guess: usize = last_guess.contents().chars().filter(|c| *c == letter).count();
+        let in_secret: usize = secret.contents().chars().filter(|c| *c == letter).count();
+        let in_attempt: usize = attempt.contents().chars().filter(|c| *c == letter).count();
+
+        // "for each letter in the previous guess, the attempt must contain at least as many of that letter as are in the last guess or in the secret word, whichever has fewer"
+        if in_attempt < std::cmp::min(in_guess, in_secret) {
+            return Err(GuessError::HardModeMissingLetter { letter, position: None });
+        }
+
+        /*
+            To see why this works:
+            E R R O R -> last guess     M A R R Y (secret word)
+            Error would get two matches (Y & G), which means you need to have two in your next attempt
+            If the words were reversed it would still be true, Marry would have a green and a yellow and your next guess would have to include them
+            If you chose a word with different R positioning and all you got was yellows, it would still hold true.
+        */
+
+        /*
+            there is an ever so slight bug where if you've revealed the first letter as green, but there's another
+            of that letter revealed as yellow later in the word, a different yellow letter before it will not get
+            "error priority". e.g. "T R E A T", where the letter 1 is green and 4/5 are yellow, if you type "TRAPS",
+            the message will be "Guess must contain T". Which I think is fine, because the end result is the same
+            I'm pretty sure real Wordle displays the "first" error in the word, but this is an extremely fringe case
+            with no real effect on the outcome
+        */
+    }
+
+    Ok(())
 }
 
 struct Keyboard {
@@ -771,60 +1642,52 @@ impl Keyboard {
         }
     }
 
-    fn format(&self, coord: (u16, u16), contrast: bool) -> String { // have to pass in some board struct fields, ah well
+    fn draw(&self, screen: &mut impl Renderer, coord: (u16, u16), theme: &Theme, colours: ColourSupport, layout: KeyboardLayout) { // have to pass in some board struct fields, ah well
         // coord in this case is where the keyboard starts, not the game board
-        let qwerty_sequence: String = String::from(" _QWERTYUIOP __ASDFGHJKL ____ZXCVBNM");
-        let mut _buf = String::new();
         let (col, mut row) = coord;
-        _buf = format!("{}", cursor::Goto(col, row));
-        for chars in qwerty_sequence.chars() {
+        let mut line = Vec::new();
+        for chars in layout.sequence().chars() {
             if chars.is_whitespace() {
-                row += 1;
-                _buf = format!("{_buf}{}", cursor::Goto(col, row)); // newline
+                screen.print_spans(col, row, &line);
+                line.clear();
+                row += 1; // newline
             } else if chars == '_' {
-                _buf = format!("{_buf} "); // for keyboard alignment
+                line.push(Span::plain(" ")); // for keyboard alignment
             } else { // if it's a normal letter ...
                 match self.guessed_letters.get(&chars) { // ... print it depending on its guess "status"
-                    Some(Letter::Green) => {
-                        if contrast {
-                            _buf = format!("{_buf}{} ", chars.to_string().bright_magenta());
-                        } else {
-                            _buf = format!("{_buf}{} ", chars.to_string().bright_green());
-                        }
-                    },
-                    Some(Letter::Yellow) => {
-                        if contrast {
-                            _buf = format!("{_buf}{} ", chars.to_string().bright_cyan());
-                        } else {
-                            _buf = format!("{_buf}{} ", chars.to_string().bright_yellow());
-                        }
-                    },
-                    Some(Letter::Grey) => _buf = format!("{_buf}{} ", chars.to_string().truecolor(10, 10, 10)),
-                    None => _buf = format!("{_buf}{chars} "), // if that letter has not been guessed, print it normally
+                    Some(letter) => line.push(theme.paint(&chars.to_string(), *letter, colours)),
+                    None => line.push(Span::plain(chars)), // if that letter has not been guessed, print it normally
                 }
+                line.push(Span::plain(" "));
             }
         }
-        format!("{_buf}\n");
-        _buf
+        screen.print_spans(col, row, &line);
+    }
+
+    fn draw_inline(&self, screen: &mut impl Renderer, coord: (u16, u16), theme: &Theme, colours: ColourSupport, layout: KeyboardLayout) {
+        // the compact layout's keyboard: every letter on one line, in the layout's order
+        let line: Vec<Span> = layout.sequence().chars().filter(|c| c.is_ascii_uppercase()).map(|chars| match self.guessed_letters.get(&chars) {
+            Some(letter) => theme.paint(&chars.to_string(), *letter, colours),
+            None => Span::plain(chars),
+        }).collect();
+        screen.print_spans(coord.0, coord.1, &line);
     }
 }
 
-pub fn press_to_continue() {
-    // suspends program while waiting for user to press a key
-    let input = stdin();
-    for key in input.keys() {
-        match key.unwrap() {
-            _ => break,
-        }
+fn shorten(msg: &str) -> &str {
+    // messages for the compact layout, which only has the width of the keyboard to print them in
+    match msg {
+        "Saved game resumed" => "Resumed",
+        _ => msg,
     }
 }
 
-fn check_terminal() -> Result<(), &'static str> { // checks if terminal window is big enough to accommodate game
-    let (width, height) = termion::terminal_size().unwrap();
-    if width < 50 || height < 22 {
-        return Err("Please resize the terminal to at least 50 x 22\nPress Enter to retry");
+fn check_terminal() -> Result<(), String> { // checks if terminal window is big enough to accommodate game (in the compact layout at least)
+    if !Layout::fits(termion::terminal_size().unwrap(), WORD_LENGTH, GUESSES) {
+        let (width, height) = Layout::smallest(WORD_LENGTH, GUESSES);
+        Err(format!("Please resize the terminal to at least {width} x {height}\nPress Enter to retry"))
     } else {
-        return Ok(());
+        Ok(())
     }
 }
 
@@ -832,8 +1695,7 @@ pub fn enforce_terminal() {
     // enforces terminal size - this loops until terminal is the proper size. Called on program start, before entering alt screen
     while let Err(error) = check_terminal() {
         println!("{error}"); // prints "please resize terminal" message
-        let input = stdin();
-        for key in input.keys() {
+        for key in stdin_keys() {
             match key.unwrap() {
                 Key::Char('\n') => {
                     break; // pressing Enter breaks the for and lets the while let try again
@@ -843,4 +1705,188 @@ pub fn enforce_terminal() {
             }
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use render::{Colour, GridRenderer};
+
+    const BOARD: &str = r"
+                        W O R D L E
+
+                   ---------------------
+                   | S | L | A | T | E |
+                   ---------------------
+                   | C | R | A | N | E |
+                   ---------------------
+                   |   |   |   |   |   |
+                   ---------------------
+                   |   |   |   |   |   |
+                   ---------------------
+                   |   |   |   |   |   |
+                   ---------------------
+                   |   |   |   |   |   |
+                   ---------------------
+
+                        Magnificent
+
+                    Q W E R T Y U I O P
+                     A S D F G H J K L
+                       Z X C V B N M
+
+                 Press any key to continue";
+
+    const STATS: &str = r"
+                          Normal
+
+  1          100        2.0        1          1
+  Played     Win %      Avg        Current    Max
+                                   Streak     Streak
+
+| 1 | 0
+| 2 ||||||||||||||||||||||||||||||||||||||||||||||| 1
+| 3 | 0
+| 4 | 0
+| 5 | 0
+| 6 | 0
+| X | 0
+
+Trend    █
+Openers  SLATE 1 (100%)
+
+
+                        Stats saved
+
+                   Press any key to exit";
+
+    fn test_board(profile: &str, keys: &str) -> Board<GridRenderer> {
+        // a board on a screen in memory, playing as a throwaway profile
+        let profile = profile::test_profile(profile);
+        let keys: VecDeque<Key> = keys.chars().map(Key::Char).collect();
+        let secret = Word::try_new(String::from("crane"), &Guesses::Builtin).unwrap();
+        let mut board = Board::with_io(secret, GridRenderer::new(60, 30), Box::new(keys));
+        board.profile = profile;
+        board.motion = Motion { animate: false, speed: 100.0 }; // nothing to wait for
+        board.colours = ColourSupport::TrueColor; // whatever the terminal running the tests says
+        board
+    }
+
+    #[test]
+    fn game_snapshots() {
+        // a whole game, checking what the board and keyboard look like when it's won, then the stats
+        let mut board = test_board("snapshots", "\nslate\ncrane\n    "); // spaces to get past the game over screens
+
+        // the same steps as main's game loop
+        board.welcome();
+        board.draw();
+        for turn in 1..=2 {
+            board.turn = turn;
+            let guess = Word::try_new(board.get_input(), &Guesses::Builtin).unwrap();
+            board.guesses.push(guess);
+            board.draw();
+        }
+        assert!(board.check_guess());
+        board.win = true;
+        board.win_message();
+        assert_eq!(board.screen().text().trim_start_matches('\n'), &BOARD[1..]);
+        let style = |rows: std::ops::RangeInclusive<u16>, letter: char| { // of the first of that letter on those rows
+            let screen = board.screen();
+            let mut cells = rows.flat_map(|row| (1..=60).filter_map(move |col| screen.cell(col, row)));
+            cells.find(|cell| cell.ch == letter).unwrap().style
+        };
+        // the text doesn't show the keyboard's colours, so check its keys took the same ones as their tiles
+        let (keys, first, second) = (board.geometry.keyboard().1..=board.geometry.keyboard().1 + 4, board.geometry.guess_row(1), board.geometry.guess_row(2));
+        let (green, grey) = (style(second..=second, 'C'), style(first..=first, 'S'));
+        assert_eq!(green, Style { fg: Some(Colour::Rgb(106, 170, 100)), ..Style::default() }); // the dark theme's
+        assert_ne!(green, grey);
+        assert_eq!(style(keys.clone(), 'C'), green);
+        assert_eq!(style(keys.clone(), 'A'), green);
+        assert_eq!(style(keys.clone(), 'S'), grey);
+        assert_eq!(style(keys, 'Q'), Style::default()); // not guessed
+
+        board.stats();
+        let _ = std::fs::remove_dir_all(board.profile.dir());
+        assert_eq!(board.screen().text().trim_start_matches('\n'), &STATS[1..]);
+    }
+
+    #[test]
+    fn replay_for_a_fresh_profile() {
+        // the stats after a replay are the watcher's, and a profile that's never played has none at all
+        let mut board = test_board("fresh-replay", "  ");
+        board.replay = Some(Recording::new(Mode::Daily(100), "CRANE", false, "dark", KeyboardLayout::Qwerty, KeyBindings::default()));
+        board.mode = Mode::Daily(100);
+        board.turn = 1;
+        board.win = true;
+        board.stats();
+        let text = board.screen().text();
+        assert!(text.contains("Daily"), "{text}");
+        assert!(text.contains("Replay, stats not saved"), "{text}");
+        assert!(!board.profile.dir().exists()); // and nothing got saved
+    }
+
+    #[test]
+    fn replay_settings_match() {
+        // a replay's settings menu has to be the one the recording was made with, or the recorded Up/Down presses land elsewhere
+        let mut board = test_board("replay-settings", "");
+        let shown = board.settings_shown();
+        board.replay = Some(Recording::new(Mode::Normal, "CRANE", false, "dark", KeyboardLayout::Qwerty, KeyBindings::default()));
+        assert_eq!(board.settings_shown(), shown);
+        assert_eq!(shown.last(), Some(&Setting::Defaults));
+        board.overlay = Some(Overlay::Settings(shown.len() - 1, None));
+        board.change_setting(Setting::Defaults); // does nothing, not even say it's saved
+        assert!(matches!(board.overlay, Some(Overlay::Settings(_, None))));
+    }
+
+    #[test]
+    fn stats_tabs_fit() {
+        // every mode, hard and not, is too many tabs for a narrow screen, so they scroll to keep the selected one showing
+        let mut board = test_board("tabs", "");
+        let mut book = stats::StatsBook::default();
+        for mode in [Mode::Normal, Mode::Daily(100), Mode::Race, Mode::Challenge] {
+            book.get_mut(&stats::key(mode.name(), false)).record(true, 3);
+            book.get_mut(&stats::key(mode.name(), true)).record(true, 3);
+        }
+        let tabs = book.keys();
+        let selected = tabs.iter().position(|tab| tab == "race").unwrap();
+        let stats_screen = StatsScreen { book, records: Vec::new(), tabs, selected, saved: None, history_error: None, prompt: false };
+        board.draw_stats(&stats_screen);
+        let text = board.screen().text();
+        let bar = text.lines().nth(board.geometry.stats_tabs_row() as usize - 1).unwrap();
+        assert!(bar.contains("< ") && bar.contains("Race  ") && bar.contains("Race*") && !bar.contains("Challenge"), "{text}");
+        assert!(bar.trim_end().chars().count() <= board.geometry.stats().width as usize + 2, "{text}");
+    }
+
+    fn word(word: &str) -> Word {
+        Word::try_new(String::from(word), &Guesses::Builtin).unwrap()
+    }
+
+    #[test]
+    fn bad_words() {
+        let guesses = Guesses::Builtin;
+        assert_eq!(Word::try_new(String::from("cran"), &guesses).unwrap_err(), GuessError::WrongLength(4));
+        assert_eq!(Word::try_new(String::from("cranes"), &guesses).unwrap_err(), GuessError::WrongLength(6));
+        assert_eq!(Word::try_new(String::from("straße"), &guesses).unwrap_err(), GuessError::WrongLength(6)); // counted in letters, not bytes
+        assert_eq!(Word::try_new(String::from("cr4ne"), &guesses).unwrap_err(), GuessError::NonAlphabetic(2));
+        assert_eq!(Word::try_new(String::from("crâne"), &guesses).unwrap_err(), GuessError::NonAlphabetic(2));
+        assert_eq!(Word::try_new(String::from("xqzvj"), &guesses).unwrap_err(), GuessError::NotInWordList);
+        assert_eq!(word("CrAnE").contents(), "CRANE");
+    }
+
+    #[test]
+    fn hard_mode_rules() {
+        let secret = word("crane");
+        assert_eq!(hard_check(&secret, None, &word("fuzzy")), Ok(())); // nothing revealed on the first guess
+
+        // CLOUD: C is green, so it has to stay first
+        let last = word("cloud");
+        assert_eq!(hard_check(&secret, Some(&last), &word("chart")), Ok(()));
+        assert_eq!(hard_check(&secret, Some(&last), &word("black")), Err(GuessError::HardModeMissingLetter { letter: 'C', position: Some(0) }));
+
+        // NAILS: N and A are yellow, so they have to be in there somewhere
+        let last = word("nails");
+        assert_eq!(hard_check(&secret, Some(&last), &word("snare")), Ok(()));
+        assert_eq!(hard_check(&secret, Some(&last), &word("amber")), Err(GuessError::HardModeMissingLetter { letter: 'N', position: None }));
+        assert_eq!(GuessError::HardModeMissingLetter { letter: 'C', position: Some(0) }.to_string(), "1st letter must be C");
+        assert_eq!(GuessError::HardModeMissingLetter { letter: 'N', position: None }.to_string(), "Guess must contain N");
+    }
+}
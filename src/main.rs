@@ -1,23 +1,222 @@
-mod valid_guesses;
-mod secret_words;
-
-use wordle::{Board, Word};
-use crate::valid_guesses::ValidGuesses;
-use crate::secret_words::SecretWords;
+use wordle::{Board, Mode, Word, stats, history, daily};
+use wordle::config::Config;
+use wordle::animation::Motion;
+use wordle::race::{self, Race, RaceHost};
+use wordle::challenge;
+use wordle::profile::{self, Profile};
+use wordle::recording;
+use wordle::plain::PlainGame;
+use wordle::words::{Guesses, Secrets};
 
 // A TUI reconstruction of Wordle by Matt Sellick
-// Randomly selects a secret word on every launch
-// Saves a text file to the working directory when the game ends to store stats
+// Randomly selects a secret word on every launch (or the day's word with --daily)
+// Saves stats to the user's data directory (~/.local/share/wordle) when the game ends, separately for each profile
+
+const USAGE: &str = "Usage: wordle [--daily] [--hard] [--plain | --accessible] [--profile NAME]
+                    [--reduced-motion] [--speed N]
+       wordle host [--port PORT] [--players N] [--name NAME]
+       wordle join ADDRESS[:PORT] [--name NAME]
+       wordle challenge [CODE]
+       wordle stats [--help]
+       wordle profiles [--mode MODE]
+       wordle replay [--list] [--profile NAME] [N | FILE]
+
+  --daily        play today's puzzle (one per day, streaks follow the calendar)
+  --hard         hard mode: revealed letters have to be used in later guesses
+  --plain        no full-screen board: read guesses a line at a time and print
+                 results like CRANE -> G.Y.. (the default when not in a terminal)
+  --accessible   like --plain but worded for screen readers, e.g.
+                 C correct, R absent, A present... (? reads the board again)
+  --profile NAME play as NAME, with their own stats, settings and saved games
+                 (created if it doesn't exist yet)
+  --reduced-motion
+                 no animations, everything appears straight away
+  --speed N      animations and pauses N times as fast, e.g. 2 or 0.5
+                 (both just for this game, the settings menu sets them for the
+                 profile. Any key skips an animation or pause)
+  profiles       compare everyone's stats side by side
+  replay         watch a recorded game again (the latest, or the Nth latest
+                 from --list), press the quit key (`) or Esc to stop
+  host           start a race on this machine for others on the network to join
+  join           join someone else's race
+  challenge      pick a secret word for someone else and get a code for it,
+                 or play the word behind a code you've been given
+  --port PORT    port to host on or join (default 7878)
+  --players N    how many racers to wait for, including you (default 2)
+  --name NAME    what the other racers see you as (default your username)
+
+In a game, Esc or F2 opens the settings menu and ? shows the keys. These can be
+changed in the [keys] section of ~/.config/wordle/config.toml, which also sets
+defaults for every profile (hard mode, theme, keyboard, animations...), word lists
+and where stats are kept. The settings menu can save its settings there too";
+
+const STATS_USAGE: &str = "Usage: wordle stats [--profile NAME] [--mode MODE] [--export csv|json] [--output FILE]
+
+Prints your stats without starting a game.
+  --profile NAME     someone else's stats (default: the default profile)
+  --mode MODE        only show one mode, e.g. normal or normal-hard
+  --export FORMAT    print every finished game as csv or json instead
+  --output FILE      write the export to FILE instead of the screen";
 
 fn main() {
+    // the config file can move where stats (and everything else) are kept, which every command needs to know first.
+    // So a broken one stops everything, rather than quietly reading (or exporting) stats from the wrong place
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => exit_with_error(&format!("Error in config: {e}")),
+    };
+    if let Some(dir) = &config.stats_dir {
+        stats::set_data_dir(dir.clone());
+    }
+
+    // subcommands that don't start a game
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("stats") {
+        stats_command(&args[1..], &config);
+        return;
+    }
+    if args.first().map(String::as_str) == Some("profiles") {
+        profiles_command(&args[1..], &config);
+        return;
+    }
+    if args.first().map(String::as_str) == Some("replay") {
+        replay_command(&args[1..], config);
+        return;
+    }
+    if args.len() == 1 && args[0] == "challenge" {
+        create_challenge(&config);
+        return;
+    }
+
+    // game options
+    let mut daily_mode = false;
+    let mut race_role: Option<&str> = None; // "host" or "join"
+    let mut address: Option<String> = None;
+    let mut port = race::DEFAULT_PORT;
+    let mut players: usize = 2;
+    let mut name = std::env::var("USER").unwrap_or_else(|_| String::from("player"));
+    let mut challenge_code: Option<&String> = None;
+    let mut profile = Profile::default();
+    let mut hard = false;
+    let mut accessible = false;
+    let mut reduced_motion = false;
+    let mut speed: Option<f64> = None;
+    let mut plain = !termion::is_tty(&std::io::stdin()) || !termion::is_tty(&std::io::stdout()); // can't draw the board without a terminal
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--daily" => daily_mode = true,
+            "--hard" => hard = true,
+            "--plain" => plain = true,
+            "--accessible" => {
+                plain = true;
+                accessible = true;
+            },
+            "host" if race_role.is_none() => race_role = Some("host"),
+            "join" if race_role.is_none() => {
+                race_role = Some("join");
+                address = args.next().cloned();
+            },
+            "challenge" if challenge_code.is_none() => match args.next() {
+                Some(code) => challenge_code = Some(code),
+                None => exit_with_error(&format!("challenge needs a code to play\n\n{USAGE}")),
+            },
+            "--port" => port = parse_option(args.next(), "--port"),
+            "--players" => players = parse_option(args.next(), "--players"),
+            "--name" => name = args.next().cloned().unwrap_or(name),
+            "--profile" => profile = parse_profile(args.next()),
+            "--reduced-motion" => reduced_motion = true,
+            "--speed" => match args.next().and_then(|s| Motion::parse_speed(s)) {
+                Some(s) => speed = Some(s),
+                None => exit_with_error(&format!("--speed needs a number like 2 or 0.5\n\n{USAGE}")),
+            },
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return;
+            },
+            other => exit_with_error(&format!("Unknown option: {other}\n\n{USAGE}")),
+        }
+    }
+    if [daily_mode, race_role.is_some(), challenge_code.is_some()].iter().filter(|m| **m).count() > 1 {
+        exit_with_error("Pick one of --daily, host/join or challenge");
+    }
+    if plain && race_role.is_some() {
+        exit_with_error("Races need the full-screen game, they can't be played with --plain or --accessible");
+    }
+
+    // one daily puzzle per day: if today's is done, say when the next one is instead of starting
+    let mode = if daily_mode {
+        let day = daily::today(&config.timezone);
+        let book = stats::load(&profile).unwrap_or_default();
+        if [false, true].iter().any(|hard| book.get(&stats::key(Mode::Daily(day).name(), *hard)).last_day == Some(day)) {
+            println!("You've already played today's Wordle. Next one in {}", daily::format_countdown(daily::until_tomorrow(&config.timezone)));
+            return;
+        }
+        Mode::Daily(day)
+    } else if race_role.is_some() {
+        Mode::Race
+    } else if challenge_code.is_some() {
+        Mode::Challenge
+    } else {
+        Mode::Normal
+    };
+
+    // a challenge code with a typo should be caught before anything else happens
+    let challenge_secret = challenge_code.map(|code| match challenge::decode(code) {
+        Ok(secret) => secret,
+        Err(e) => exit_with_error(e),
+    });
+
+    if let Err(e) = profile.create() {
+        exit_with_error(&format!("Could not create profile {}: {e}", profile.name()));
+    }
 
     // check terminal size
-    wordle::enforce_terminal();
+    if !plain {
+        wordle::enforce_terminal();
+    }
 
     // game setup
-    let valid_guesses = ValidGuesses::load().contents;
-    let secret_word = match Word::try_new(SecretWords::load().choose_secret(), &valid_guesses) { // note that secret words must also be in the valid guess list
+    let (valid_guesses, secret_words) = word_lists(&config);
+    let mut race: Option<Race> = None;
+    let secret = match (mode, race_role) {
+        (Mode::Daily(day), _) => secret_words.choose_daily(day),
+        (Mode::Challenge, _) => challenge_secret.unwrap(), // unwrap is safe, having a code is what made this Challenge mode
+        (_, Some("host")) => { // the host picks the secret for everyone, then waits for the others to show up
+            let secret = secret_words.choose_secret();
+            let host = match RaceHost::bind(("0.0.0.0", port)) {
+                Ok(host) => host,
+                Err(e) => exit_with_error(&format!("Could not host on port {port}: {e}")),
+            };
+            println!("Hosting a race on port {port}, waiting for {} more player(s)", players.saturating_sub(1));
+            match host.start(players, &name, &secret, |name| println!("{name} joined")) {
+                Ok(r) => race = Some(r),
+                Err(e) => exit_with_error(&format!("Could not start race: {e}")),
+            }
+            secret
+        },
+        (_, Some(_)) => {
+            let Some(mut address) = address else {
+                exit_with_error(&format!("Which race? Give the host's address after join\n\n{USAGE}"));
+            };
+            if !address.contains(':') {
+                address = format!("{address}:{port}");
+            }
+            println!("Joining race at {address}, waiting for it to start");
+            match Race::join(&address, &name) {
+                Ok((r, secret)) => {
+                    race = Some(r);
+                    secret
+                },
+                Err(e) => exit_with_error(&format!("Could not join race: {e}")),
+            }
+        },
+        _ => secret_words.choose_secret(),
+    };
+    let secret_word = match Word::try_new(secret, &valid_guesses) { // note that secret words must also be in the valid guess list
         Ok(w) => w,
+        Err(e) if race_role == Some("join") => exit_with_error(&format!("Host sent an invalid secret word: {e}")),
+        Err(e) if mode == Mode::Challenge => exit_with_error(&format!("That challenge isn't playable: {e}")),
         Err(e) => panic!("Error choosing secret word: {}", e), // if it can't load a secret word it should panic
     };
 
@@ -25,26 +224,58 @@ fn main() {
     // println!("\nSecret word is: {}", secret_word.contents());
     // std::thread::sleep(std::time::Duration::from_secs(2));
 
+    // plain mode plays it out on stdin/stdout instead
+    if plain {
+        let game = PlainGame { mode, hard: hard || profile::Prefs::load(&profile, &config.defaults).hard, accessible, profile: &profile, valid_guesses: &valid_guesses };
+        if let Err(e) = game.play(&secret_word, std::io::stdin().lock(), std::io::stdout()) {
+            exit_with_error(&format!("Error: {e}"));
+        }
+        return;
+    }
+
     // initialize game board, moving into alternate screen
     let mut game_board = Board::new(secret_word);
+    game_board.themes = config.themes.clone(); // before the profile, whose theme might be one of these
+    game_board.defaults = config.defaults.clone(); // and whose settings might not have been chosen yet
+    if let Some(colours) = config.colours {
+        game_board.colours = colours;
+    }
+    game_board.set_profile(profile);
+    game_board.hard |= hard; // on top of the profile's preference
+    if reduced_motion {
+        game_board.motion.animate = false;
+    }
+    if let Some(speed) = speed {
+        game_board.motion.speed = speed;
+    }
+    game_board.mode = mode;
+    game_board.timezone = config.timezone;
+    game_board.bindings = config.keys;
+    game_board.race = race;
+    play(game_board, &valid_guesses);
+}
+
+// the game itself, from the welcome screen to the stats. Replays go through here too, with the keys coming from the recording
+fn play(mut game_board: Board, valid_guesses: &Guesses) {
     game_board.welcome();
     game_board.draw();
+    game_board.resume(); // if this profile quit this kind of game part way through, carry on from there
 
     // turn loop
-    for turn in 1..=6 as usize {
+    for turn in game_board.guesses.len() + 1..=6 {
 
         // update turn in Board
         game_board.turn = turn;
 
         // get user input
         loop {
-            let guess = match Word::try_new(game_board.get_input(), &valid_guesses) { // asks for a guess word
+            let guess = match Word::try_new(game_board.get_input(), valid_guesses) { // asks for a guess word
                 Ok(g) => {
                     if game_board.hard { // if you're in hard mode, make sure it's a legal guess before binding
                         match game_board.hard_check(&g) {
                             Ok(_) => g,
                             Err(error) => {
-                                game_board.print_msg(&error);
+                                game_board.reject(&error);
                                 continue;
                             },
                         }
@@ -53,7 +284,7 @@ fn main() {
                     }
                 },
                 Err(e) => {
-                    game_board.print_msg(e);
+                    game_board.reject(&e);
                     continue;
                 },
             };
@@ -75,4 +306,236 @@ fn main() {
     game_board.win_message(); // display win message and wait for key press
     game_board.stats(); // display stats and wait for key press
     drop(game_board); // return to main screen
-}
\ No newline at end of file
+}
+
+// "wordle stats": the summary and histogram on plain stdout, or the whole game history as csv/json
+fn stats_command(args: &[String], config: &Config) {
+    let mut mode: Option<&String> = None;
+    let mut export: Option<&String> = None;
+    let mut output: Option<&String> = None;
+    let mut profile = Profile::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" => profile = parse_profile(args.next()),
+            "--mode" => mode = args.next(),
+            "--export" => export = args.next(),
+            "--output" | "-o" => output = args.next(),
+            "--help" | "-h" => {
+                println!("{STATS_USAGE}");
+                return;
+            },
+            other => exit_with_error(&format!("Unknown option: {other}\n\n{STATS_USAGE}")),
+        }
+    }
+
+    if let Some(format) = export {
+        let records = match history::load(&profile) {
+            Ok(records) => records,
+            Err(e) => exit_with_error(&format!("Could not read game history: {e}")),
+        };
+        let records: Vec<_> = records.into_iter().filter(|r| mode.is_none_or(|m| r.key == *m)).collect();
+        let text = match format.as_str() {
+            "csv" => history::to_csv(&records),
+            "json" => history::to_json(&records),
+            other => exit_with_error(&format!("Unknown export format: {other} (expected csv or json)")),
+        };
+        match output {
+            Some(path) => if let Err(e) = std::fs::write(path, text) {
+                exit_with_error(&format!("Could not write {path}: {e}"));
+            },
+            None => print!("{text}"),
+        }
+        return;
+    }
+
+    let mut book = match stats::load(&profile) {
+        Ok(book) => book,
+        Err(e) => exit_with_error(&format!("Could not read stats: {e}")),
+    };
+    book.expire_streaks(daily::today(&config.timezone)); // daily streaks end when a day is missed, even without playing
+    let keys = match mode {
+        Some(m) => vec![m.clone()],
+        None => book.keys(),
+    };
+    if keys.is_empty() {
+        println!("No games played yet");
+        return;
+    }
+    let records = history::load(&profile).unwrap_or_default(); // the summary still works without history, it just has less detail
+    let summaries: Vec<String> = keys.iter().map(|key| {
+        stats::summary(key, &book.get(key), &history::insights(&records, key, 40))
+    }).collect();
+    print!("{}", summaries.join("\n"));
+}
+
+// "wordle profiles": every profile's stats next to each other, one table per mode
+fn profiles_command(args: &[String], config: &Config) {
+    let mode = match args {
+        [] => None,
+        [option, mode] if option == "--mode" => Some(mode.clone()),
+        _ => exit_with_error(&format!("Unknown option: {}\n\n{USAGE}", args.join(" "))),
+    };
+    let today = daily::today(&config.timezone);
+    let books: Vec<_> = profile::list().into_iter().map(|p| {
+        let mut book = stats::load(&p).unwrap_or_default(); // an unreadable file just shows as no games
+        book.expire_streaks(today);
+        (p, book)
+    }).collect();
+
+    let names: Vec<&str> = books.iter().map(|(p, _)| p.name()).collect();
+    println!("Profiles: {}", names.join(", "));
+    let mut keys: Vec<String> = books.iter().flat_map(|(_, book)| book.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    if let Some(mode) = mode {
+        keys = vec![mode];
+    }
+    for key in keys {
+        print!("\n{}", profile::comparison(&key, &books));
+    }
+}
+
+// "wordle replay": play back a recorded game, or list what's been recorded
+fn replay_command(args: &[String], config: Config) {
+    let mut profile = Profile::default();
+    let mut list = false;
+    let mut which: Option<&String> = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" => profile = parse_profile(args.next()),
+            "--list" => list = true,
+            _ if which.is_none() && !arg.starts_with('-') => which = Some(arg),
+            other => exit_with_error(&format!("Unknown option: {other}\n\n{USAGE}")),
+        }
+    }
+
+    let paths = recording::list(&profile);
+    if list {
+        if paths.is_empty() {
+            println!("No recorded games yet");
+        }
+        for (number, path) in paths.iter().enumerate() {
+            match recording::load(path) {
+                Ok(r) => println!("{:>3}  {}  {:<9}  {}  {}", number + 1, history::format_timestamp(recording::timestamp(path)), r.mode.name(), r.secret, r.result()),
+                Err(e) => println!("{:>3}  {e}", number + 1),
+            }
+        }
+        return;
+    }
+
+    // a number counts back from the latest game, anything else is a file
+    let path = match which.map(|w| w.parse::<usize>()) {
+        None => paths.first().cloned(),
+        Some(Ok(number)) => number.checked_sub(1).and_then(|index| paths.get(index).cloned()),
+        Some(Err(_)) => which.map(std::path::PathBuf::from),
+    };
+    let Some(path) = path else {
+        exit_with_error("No such recording (see wordle replay --list)");
+    };
+    let recording = match recording::load(&path) {
+        Ok(recording) => recording,
+        Err(e) => exit_with_error(&e),
+    };
+
+    wordle::enforce_terminal();
+    let theme = recording.theme.clone();
+    let (valid_guesses, _) = word_lists(&config);
+    let mut game_board = Board::replay(recording, config.keys.clone()); // the keys to stop it with, the recording has its own for the game
+    game_board.theme = config.themes.get_or_default(&theme);
+    game_board.themes = config.themes;
+    if let Some(colours) = config.colours {
+        game_board.colours = colours;
+    }
+    let prefs = profile::Prefs::load(&profile, &config.defaults);
+    game_board.set_style(prefs.style); // just the look, so it's the watcher's choice
+    game_board.motion = prefs.motion;
+    game_board.profile = profile; // only for showing their stats at the end, nothing gets saved
+    play(game_board, &valid_guesses);
+}
+
+// "wordle challenge": type a secret word (hidden) and get a code for someone else to play it with
+fn create_challenge(config: &Config) {
+    let (valid_guesses, _) = word_lists(config);
+    println!("Type a secret word for someone else to guess (it won't be shown), then press Enter");
+    loop {
+        let word = match challenge::read_hidden("Secret word: ") {
+            Ok(Some(word)) => word,
+            Ok(None) => return, // changed their mind
+            Err(e) => exit_with_error(&format!("Could not read the word: {e}")),
+        };
+        // any valid guess will do, it doesn't have to be one of the usual secret words
+        match Word::try_new(word, &valid_guesses) {
+            Ok(word) => {
+                let code = challenge::encode(&word);
+                println!("Challenge code: {code}");
+                println!("They can play it with: wordle challenge {code}");
+                return;
+            },
+            Err(e) => println!("{e}, try again"),
+        }
+    }
+}
+
+fn word_lists(config: &Config) -> (Guesses, Secrets) {
+    // valid guesses and secret words: the built-in lists, unless the config file gives others
+    let read = |path: &std::path::Path| match read_word_list(path) {
+        Ok(words) => words,
+        Err(e) => exit_with_error(&format!("Error in word list {}: {e}", path.display())),
+    };
+    let guesses = match &config.guess_list {
+        Some(path) => Guesses::Custom(read(path).iter().map(|w| w.to_uppercase()).collect()),
+        None => Guesses::Builtin,
+    };
+    let secrets = match &config.secret_list {
+        Some(path) => Secrets::Custom(read(path)),
+        None => Secrets::Builtin,
+    };
+    if config.guess_list.is_some() || config.secret_list.is_some() { // the built-in ones are checked when it's built
+        if let Some(word) = (0..secrets.len()).map(|index| secrets.get(index)).find(|w| !guesses.contains(w)) {
+            exit_with_error(&format!("The secret word \"{word}\" isn't in the guess list, so it could never be guessed"));
+        }
+    }
+    (guesses, secrets)
+}
+
+fn read_word_list(path: &std::path::Path) -> Result<Vec<String>, String> {
+    // a word per line. Blank lines and # comments are skipped
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut words = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let word = line.split('#').next().unwrap_or("").trim();
+        if word.is_empty() {
+            continue;
+        }
+        if word.len() != 5 || !word.chars().all(|c| c.is_ascii_alphabetic()) { // plain a to z, tiles and challenge codes need one byte per letter
+            return Err(format!("line {}: \"{word}\" isn't five letters from a to z", number + 1));
+        }
+        words.push(word.to_lowercase());
+    }
+    if words.is_empty() {
+        return Err(String::from("no words in it"));
+    }
+    Ok(words)
+}
+
+fn parse_profile(name: Option<&String>) -> Profile {
+    match name.map(|n| Profile::new(n)) {
+        Some(Ok(profile)) => profile,
+        Some(Err(e)) => exit_with_error(e),
+        None => exit_with_error(&format!("--profile needs a name\n\n{USAGE}")),
+    }
+}
+
+fn parse_option<T: std::str::FromStr>(value: Option<&String>, option: &str) -> T {
+    match value.and_then(|v| v.parse().ok()) {
+        Some(value) => value,
+        None => exit_with_error(&format!("{option} needs a number\n\n{USAGE}")),
+    }
+}
+
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("{message}");
+    std::process::exit(1);
+}
@@ -0,0 +1,214 @@
+use std::io::{self, BufRead, Write};
+
+use crate::profile::Profile;
+use crate::words::Guesses;
+use crate::{hard_check, history, pattern, stats, Mode, Word};
+
+// plain line mode, for when there's no terminal to draw on: pipes, CI, serial consoles
+// guesses are read a line at a time from stdin and the feedback is printed as text:
+//   CRANE -> G.Y..
+// G is the right letter in the right spot, Y is in the word somewhere else, . isn't in the word
+// the rules, hard mode and stats are the same as the full-screen game
+//
+// accessible mode is the same thing worded for screen readers, nothing relies on colour or cursor movement:
+//   CRANE: C correct, R absent, A present, N absent, E absent. 5 guesses left
+// in either mode a line with just ? reads out the board and keyboard again
+
+pub struct PlainGame<'a> {
+    pub mode: Mode,
+    pub hard: bool,
+    pub accessible: bool,
+    pub profile: &'a Profile,
+    pub valid_guesses: &'a Guesses,
+}
+
+impl PlainGame<'_> {
+    pub fn play(&self, secret: &Word, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        writeln!(output, "Guess the 5-letter word in 6 tries{}", if self.hard { " (hard mode)" } else { "" })?;
+        match self.accessible {
+            true => writeln!(output, "Type a guess and press Enter. Type ? to hear the board and keyboard again")?,
+            false => writeln!(output, "G = right spot, Y = wrong spot, . = not in the word (? shows the board again)")?,
+        }
+
+        let mut guesses: Vec<Word> = Vec::new();
+        let mut lines = input.lines();
+        while guesses.len() < 6 && guesses.last().map(|g| g.contents()) != Some(secret.contents()) {
+            let Some(line) = lines.next() else {
+                writeln!(output, "No more guesses, quitting")?; // ran out of input, the game doesn't count
+                return Ok(());
+            };
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line == "?" {
+                self.read_board(secret, &guesses, &mut output)?;
+                continue;
+            }
+            let guess = match Word::try_new(line.to_string(), self.valid_guesses) {
+                Ok(guess) => guess,
+                Err(e) => {
+                    writeln!(output, "{}: {e}", line.to_uppercase())?;
+                    continue;
+                },
+            };
+            if self.hard {
+                if let Err(e) = hard_check(secret, guesses.last(), &guess) {
+                    writeln!(output, "{}: {e}", guess.contents())?;
+                    continue;
+                }
+            }
+            guesses.push(guess);
+            let guess = guesses.last().unwrap(); // just pushed
+            match self.accessible {
+                true => writeln!(output, "{}: {}. {}", guess.contents(), describe(secret, guess), guesses_left(guesses.len()))?,
+                false => writeln!(output, "{} -> {}", guess.contents(), pattern(secret, guess))?,
+            }
+        }
+
+        let win = guesses.last().map(|g| g.contents()) == Some(secret.contents());
+        match win {
+            true => writeln!(output, "Solved in {}/6", guesses.len())?,
+            false => writeln!(output, "Failure: {}", secret.contents())?,
+        }
+
+        // same stats as the full-screen game
+        let key = stats::key(self.mode.name(), self.hard);
+        let game = history::GameRecord::new(key.clone(), secret.contents().clone(), guesses.iter().map(|g| g.contents().clone()).collect(), win);
+        let day = match self.mode {
+            Mode::Daily(day) => Some(day),
+            _ => None,
+        };
+        match stats::record(self.profile, &game, day) {
+            Ok(saved) => {
+                if let Some(backup) = saved.backup {
+                    writeln!(output, "Stats file was corrupted and has been backed up to {}", backup.display())?;
+                }
+                if let Some(e) = saved.history_error {
+                    writeln!(output, "Stats saved, but not the game history: {e}")?;
+                }
+                let stats = saved.book.get(&key);
+                if self.accessible {
+                    write!(output, "\n{}", stats::spoken_summary(&key, &stats))?;
+                } else {
+                    let records = history::load(self.profile).unwrap_or_default();
+                    write!(output, "\n{}", stats::summary(&key, &stats, &history::insights(&records, &key, 40)))?;
+                }
+            },
+            Err(e) => writeln!(output, "Could not save stats: {e}")?,
+        }
+        Ok(())
+    }
+
+    fn read_board(&self, secret: &Word, guesses: &[Word], output: &mut impl Write) -> io::Result<()> {
+        // every guess so far, then what's known about each letter (like the on-screen keyboard)
+        if guesses.is_empty() {
+            writeln!(output, "No guesses yet")?;
+        }
+        for (turn, guess) in guesses.iter().enumerate() {
+            match self.accessible {
+                true => writeln!(output, "Guess {}, {}: {}", turn + 1, guess.contents(), describe(secret, guess))?,
+                false => writeln!(output, "{} -> {}", guess.contents(), pattern(secret, guess))?,
+            }
+        }
+        writeln!(output, "{}", guesses_left(guesses.len()))?;
+
+        // best thing known about each letter: correct beats present beats absent
+        let mut known: Vec<(char, char)> = Vec::new();
+        for guess in guesses {
+            for (letter, colour) in guess.contents().chars().zip(pattern(secret, guess).chars()) {
+                let rank = |c: char| match c {
+                    'G' => 2,
+                    'Y' => 1,
+                    _ => 0,
+                };
+                match known.iter_mut().find(|(l, _)| *l == letter) {
+                    Some((_, best)) if rank(colour) > rank(*best) => *best = colour,
+                    Some(_) => (),
+                    None => known.push((letter, colour)),
+                }
+            }
+        }
+        let letters = |colour: char| {
+            let mut list: Vec<String> = known.iter().filter(|(_, c)| *c == colour).map(|(l, _)| l.to_string()).collect();
+            list.sort();
+            if list.is_empty() { String::from("none") } else { list.join(", ") }
+        };
+        let unused: Vec<String> = ('A'..='Z').filter(|l| !known.iter().any(|(k, _)| k == l)).map(String::from).collect();
+        writeln!(output, "Correct: {}", letters('G'))?;
+        writeln!(output, "Present: {}", letters('Y'))?;
+        writeln!(output, "Absent: {}", letters('.'))?;
+        writeln!(output, "Not tried: {}", if unused.is_empty() { String::from("none") } else { unused.join(", ") })
+    }
+}
+
+fn describe(secret: &Word, guess: &Word) -> String {
+    // "C correct, R absent, A present, N absent, E absent"
+    let words: Vec<String> = guess.contents().chars().zip(pattern(secret, guess).chars()).map(|(letter, colour)| {
+        let status = match colour {
+            'G' => "correct",
+            'Y' => "present",
+            _ => "absent",
+        };
+        format!("{letter} {status}")
+    }).collect();
+    words.join(", ")
+}
+
+fn guesses_left(used: usize) -> String {
+    match 6 - used {
+        1 => String::from("1 guess left"),
+        left => format!("{left} guesses left"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(profile: &str, hard: bool, accessible: bool, input: &str) -> String {
+        let profile = crate::profile::test_profile(profile);
+        let game = PlainGame { mode: Mode::Normal, hard, accessible, profile: &profile, valid_guesses: &Guesses::Builtin };
+        let secret = Word::try_new(String::from("crane"), &Guesses::Builtin).unwrap();
+        let mut output = Vec::new();
+        game.play(&secret, input.as_bytes(), &mut output).unwrap();
+        let _ = std::fs::remove_dir_all(profile.dir());
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn win_with_rejections() {
+        let output = play("plain-hard", true, false, "abc\nslate\npious\n?\ncrane\n");
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[..10], [
+            "Guess the 5-letter word in 6 tries (hard mode)",
+            "G = right spot, Y = wrong spot, . = not in the word (? shows the board again)",
+            "ABC: Please choose a 5-letter word",
+            "SLATE -> ..G.G",
+            "PIOUS: 3rd letter must be A", // hard mode keeps the green A
+            "SLATE -> ..G.G", // ? goes over the board again
+            "5 guesses left",
+            "Correct: A, E",
+            "Present: none",
+            "Absent: L, S, T",
+        ]);
+        assert_eq!(lines[11..13], ["CRANE -> GGGGG", "Solved in 2/6"]);
+        assert!(output.contains("\nNormal*\n1 Played   100 Win %"), "{output}");
+    }
+
+    #[test]
+    fn accessible_wording() {
+        let output = play("plain-accessible", false, true, "?\nxxxxx\ncrane\n");
+        assert!(output.contains("No guesses yet\n6 guesses left\n"), "{output}");
+        assert!(output.contains("XXXXX: Not in word list\n"), "{output}");
+        assert!(output.contains("CRANE: C correct, R correct, A correct, N correct, E correct. 5 guesses left\n"), "{output}");
+        assert!(output.contains("Normal stats: 1 played, 100 percent won"), "{output}");
+    }
+
+    #[test]
+    fn running_out_of_input() {
+        let output = play("plain-quit", false, false, "slate\n");
+        assert!(output.ends_with("SLATE -> ..G.G\nNo more guesses, quitting\n"), "{output}");
+    }
+}
@@ -0,0 +1,221 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::{config, stats, KeyboardLayout, Mode};
+use crate::tiles::BoardStyle;
+use crate::animation::Motion;
+
+// named profiles, for when several people share a machine
+// each profile has its own stats, history, preferences and unfinished games, all kept in its own directory:
+// the default profile uses the data directory itself (so stats from before profiles existed carry on as theirs),
+// named profiles use profiles/<name> inside it
+
+const PROFILES_DIR: &str = "profiles";
+const PREFS_FILE: &str = "prefs.toml";
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    name: String, // checked by Profile::new, since it becomes a directory name
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Result<Profile, &'static str> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > 20 || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err("Profile names can only use letters, numbers, - and _ (up to 20 characters)");
+        }
+        Ok(Profile { name: name.to_lowercase() })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.name == DEFAULT_PROFILE
+    }
+
+    pub fn dir(&self) -> PathBuf {
+        match self.is_default() {
+            true => stats::data_dir(),
+            false => stats::data_dir().join(PROFILES_DIR).join(&self.name),
+        }
+    }
+
+    pub fn create(&self) -> io::Result<()> { // so a new profile shows up in the switcher before it's played a game
+        fs::create_dir_all(self.dir())
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Profile {
+        Profile { name: DEFAULT_PROFILE.to_string() }
+    }
+}
+
+pub fn list() -> Vec<Profile> {
+    // the default profile first, then everyone else alphabetically
+    let mut names: Vec<String> = match fs::read_dir(stats::data_dir().join(PROFILES_DIR)) {
+        Ok(entries) => entries.flatten()
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().to_str().map(String::from))
+            .collect(),
+        Err(_) => Vec::new(), // no named profiles yet
+    };
+    names.sort();
+    let mut profiles = vec![Profile::default()];
+    profiles.extend(names.iter().filter_map(|n| Profile::new(n).ok()).filter(|p| !p.is_default()));
+    profiles
+}
+
+// things each profile sets for itself, saved in its prefs.toml whenever they're changed
+// a profile that hasn't set them yet gets the config file's defaults, which are written the same way (see config.rs)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Prefs {
+    pub hard: bool,
+    pub theme: Option<String>, // None for the config's default theme
+    pub layout: KeyboardLayout,
+    pub style: BoardStyle,
+    pub motion: Motion,
+}
+
+impl Prefs {
+    pub fn load(profile: &Profile, defaults: &Prefs) -> Prefs {
+        // anything missing or unreadable is left at its default, preferences aren't worth refusing to start over
+        let mut prefs = defaults.clone();
+        let Ok(text) = fs::read_to_string(profile.dir().join(PREFS_FILE)) else { return prefs };
+        let Ok(values) = config::parse_values(&text) else { return prefs };
+        for (key, value) in &values {
+            match key.as_str() {
+                "hard" => prefs.hard = value == "true",
+                "theme" => prefs.theme = Some(value.to_string()),
+                "layout" => prefs.layout = KeyboardLayout::parse(value).unwrap_or_default(),
+                "style" => prefs.style = BoardStyle::parse(value).unwrap_or_default(),
+                "animations" => prefs.motion.animate = value != "false",
+                "speed" => prefs.motion.speed = Motion::parse_speed(value).unwrap_or(1.0),
+                _ => (),
+            }
+        }
+        prefs
+    }
+
+    pub fn save(&self, profile: &Profile) -> io::Result<()> {
+        let dir = profile.dir();
+        fs::create_dir_all(&dir)?;
+        let text: String = self.values().iter().map(|(key, value)| format!("{key} = {value}\n")).collect();
+        stats::write_atomic(&dir.join(PREFS_FILE), &text)
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, String)> { // as they're written to a file, strings quoted
+        let mut values = vec![
+            ("hard", self.hard.to_string()),
+            ("layout", format!("\"{}\"", self.layout.name())),
+            ("style", format!("\"{}\"", self.style.name())),
+            ("animations", self.motion.animate.to_string()),
+            ("speed", self.motion.speed.to_string()),
+        ];
+        if let Some(theme) = &self.theme {
+            values.push(("theme", format!("\"{theme}\"")));
+        }
+        values
+    }
+}
+
+// a game quit part way through, to be picked up next time the same kind of game is started
+// there's one per mode, so starting a daily doesn't throw away an unfinished normal game
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedGame {
+    pub secret: String,
+    pub guesses: Vec<String>,
+    pub hard: bool,
+    pub day: Option<i64>, // which day's puzzle, for daily games
+}
+
+impl SavedGame {
+    fn path(profile: &Profile, mode: Mode) -> PathBuf {
+        profile.dir().join(format!("saved_{}.txt", mode.name()))
+    }
+
+    pub fn load(profile: &Profile, mode: Mode, secret: &str) -> Option<SavedGame> {
+        // secret is the word this game would otherwise be played with. Normal games have a random one so it doesn't matter,
+        // but a challenge only resumes with the same code, and a daily only on the same day
+        if mode == Mode::Race {
+            return None; // a race can't be paused
+        }
+        let text = fs::read_to_string(SavedGame::path(profile, mode)).ok()?;
+        let values = config::parse_values(&text).ok()?;
+        let word = |w: &str| w.len() == 5 && w.chars().all(|c| c.is_ascii_uppercase());
+        let saved = SavedGame {
+            secret: values.get("secret").filter(|s| word(s))?.clone(),
+            guesses: values.get("guesses")?.split(',').filter(|g| !g.is_empty()).map(String::from).collect(),
+            hard: values.get("hard").is_some_and(|h| h == "true"),
+            day: values.get("day").and_then(|d| d.parse().ok()),
+        };
+        if saved.guesses.len() >= 6 || saved.guesses.iter().any(|g| !word(g) || *g == saved.secret) {
+            return None; // finished (or mangled), nothing to carry on with
+        }
+        match mode {
+            Mode::Daily(day) if saved.day != Some(day) => None, // yesterday's puzzle
+            Mode::Challenge if saved.secret != secret => None, // a different challenge
+            _ => Some(saved),
+        }
+    }
+
+    pub fn save(&self, profile: &Profile, mode: Mode) -> io::Result<()> {
+        let dir = profile.dir();
+        fs::create_dir_all(&dir)?;
+        let mut text = format!("secret = \"{}\"\nguesses = \"{}\"\nhard = {}\n", self.secret, self.guesses.join(","), self.hard);
+        if let Some(day) = self.day {
+            text.push_str(&format!("day = {day}\n"));
+        }
+        stats::write_atomic(&SavedGame::path(profile, mode), &text)
+    }
+
+    pub fn clear(profile: &Profile, mode: Mode) {
+        let _ = fs::remove_file(SavedGame::path(profile, mode)); // usually there isn't one
+    }
+}
+
+pub fn comparison(key: &str, profiles: &[(Profile, stats::StatsBook)]) -> String {
+    // one column per profile, for "wordle profiles"
+    //   Normal       default     alice       bob
+    //   Played            40        12         3
+    //   ...
+    let mut rows: Vec<(String, Vec<String>)> = vec![(stats::label(key), profiles.iter().map(|(p, _)| p.name().to_string()).collect())];
+    let all: Vec<stats::Stats> = profiles.iter().map(|(_, book)| book.get(key)).collect();
+    let mut add = |label: &str, value: &dyn Fn(&stats::Stats) -> String| {
+        rows.push((label.to_string(), all.iter().map(value).collect()));
+    };
+    add("Played", &|s| s.played().to_string());
+    add("Win %", &|s| s.win_percentage().to_string());
+    add("Avg", &|s| s.average_guesses().map(|a| format!("{a:.1}")).unwrap_or(String::from("-")));
+    add("Streak", &|s| s.streak.to_string());
+    add("Max streak", &|s| s.max_streak.to_string());
+    for turn in 0..6 {
+        add(&format!("Won in {}", turn + 1), &|s| s.wins[turn].to_string());
+    }
+    add("Failed", &|s| s.failures.to_string());
+
+    let width = profiles.iter().map(|(p, _)| p.name().len()).max().unwrap_or(0).max(6) + 2;
+    let mut out = String::new();
+    for (label, values) in rows {
+        out.push_str(&format!("{label:<12}"));
+        for value in values {
+            out.push_str(&format!("{value:>width$}"));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+pub(crate) fn test_profile(name: &str) -> Profile {
+    // for tests that save things: a profile of its own (so tests can run side by side) in a throwaway data directory.
+    // Not the default profile, which would pick up any old stats lying around in the working directory
+    stats::set_data_dir(std::env::temp_dir().join(format!("wordle-test-{}", std::process::id())));
+    let profile = Profile::new(name).unwrap();
+    let _ = fs::remove_dir_all(profile.dir()); // anything left from a run that failed part way
+    profile
+}
@@ -0,0 +1,330 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// head-to-head races over the LAN
+// one game hosts (picks the secret and relays everyone's progress), the others join it over TCP
+// only colours are ever shared once the game starts, never letters, so nobody can copy a guess
+//
+// the protocol is one line per message:
+//   joining player -> host:   HELLO <name>
+//   host -> joining player:   WELCOME <your id>, PLAYER <id> <name> (once per player), START <secret>
+//   during the game:          ROW <pattern> / DONE <1 or 0> from a player,
+//                             ROW <id> <pattern> / DONE <id> <1 or 0> / LEFT <id> relayed by the host
+// patterns are five characters: G (green), Y (yellow) or . (grey)
+// the host is always player 0
+
+pub const DEFAULT_PORT: u16 = 7878;
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5); // how long a new connection gets to say who it is
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub name: String,
+    pub rows: Vec<String>, // patterns of each guess so far
+    pub result: Option<bool>, // Some(won) once they've finished
+    pub connected: bool,
+}
+
+impl Player {
+    fn new(name: &str) -> Player {
+        Player { name: name.to_string(), rows: Vec::new(), result: None, connected: true }
+    }
+}
+
+struct Shared {
+    players: Vec<Player>,
+    generation: u64, // bumped on every change so the board knows when to redraw
+}
+
+enum Link {
+    Host(Arc<Mutex<Vec<Option<TcpStream>>>>), // a stream per player id (None for the host itself and anyone who left)
+    Client(TcpStream),
+}
+
+pub struct Race {
+    me: usize,
+    shared: Arc<Mutex<Shared>>,
+    link: Link,
+}
+
+impl Race {
+    pub fn join<A: ToSocketAddrs>(address: A, name: &str) -> io::Result<(Race, String)> {
+        // connects to a host and waits for the race to start. Returns the race and the secret word
+        let stream = TcpStream::connect(address)?;
+        let mut writer = stream.try_clone()?;
+        writeln!(writer, "HELLO {}", clean_name(name))?;
+
+        let mut reader = BufReader::new(stream);
+        let mut me: Option<usize> = None;
+        let mut players: Vec<Player> = Vec::new();
+        let secret = loop {
+            let line = read_line(&mut reader)?.ok_or_else(|| protocol_error("host closed the connection before the race started"))?;
+            match line.split_once(' ') {
+                Some(("WELCOME", id)) => me = id.parse().ok(),
+                Some(("PLAYER", rest)) => {
+                    let (id, name) = rest.split_once(' ').ok_or_else(|| protocol_error("bad player line"))?;
+                    let id: usize = id.parse().map_err(|_| protocol_error("bad player id"))?;
+                    if players.len() <= id {
+                        players.resize(id + 1, Player::new(""));
+                    }
+                    players[id] = Player::new(name);
+                },
+                Some(("START", secret)) => break secret.to_string(),
+                _ => return Err(protocol_error(&format!("unexpected message from host: {line}"))),
+            }
+        };
+        let me = me.ok_or_else(|| protocol_error("host never said which player we are"))?;
+
+        let shared = Arc::new(Mutex::new(Shared { players, generation: 0 }));
+        let thread_shared = Arc::clone(&shared);
+        thread::spawn(move || { // apply everything the host relays until the connection drops
+            while let Ok(Some(line)) = read_line(&mut reader) {
+                let mut parts = line.splitn(3, ' ');
+                let (kind, id, rest) = (parts.next(), parts.next().and_then(|id| id.parse::<usize>().ok()), parts.next());
+                if let (Some(kind), Some(id)) = (kind, id) {
+                    apply(&thread_shared, id, kind, rest.unwrap_or(""));
+                }
+            }
+            apply(&thread_shared, 0, "LEFT", ""); // lost the host, so nobody else will be heard from either
+        });
+
+        Ok((Race { me, shared, link: Link::Client(writer) }, secret))
+    }
+
+    pub fn send_row(&mut self, pattern: &str) {
+        self.send("ROW", pattern);
+    }
+
+    pub fn send_done(&mut self, win: bool) {
+        self.send("DONE", if win { "1" } else { "0" });
+    }
+
+    fn send(&mut self, kind: &str, value: &str) {
+        // errors are ignored: if the connection's gone the race just carries on locally
+        apply(&self.shared, self.me, kind, value);
+        match &mut self.link {
+            Link::Client(stream) => {
+                let _ = writeln!(stream, "{kind} {value}");
+            },
+            Link::Host(streams) => broadcast(streams, self.me, &format!("{kind} {} {value}", self.me)),
+        }
+    }
+
+    pub fn opponents(&self) -> Vec<Player> {
+        let shared = self.shared.lock().unwrap();
+        shared.players.iter().enumerate().filter(|(id, _)| *id != self.me).map(|(_, p)| p.clone()).collect()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.shared.lock().unwrap().generation
+    }
+}
+
+impl Drop for Race {
+    fn drop(&mut self) {
+        // the reader threads hold their own handles to the sockets, so close them explicitly to let everyone know we've gone
+        match &self.link {
+            Link::Client(stream) => {
+                let _ = stream.shutdown(Shutdown::Both);
+            },
+            Link::Host(streams) => for stream in streams.lock().unwrap().iter().flatten() {
+                let _ = stream.shutdown(Shutdown::Both);
+            },
+        }
+    }
+}
+
+// a race that's been set up but hasn't started yet
+pub struct RaceHost {
+    listener: TcpListener,
+}
+
+impl RaceHost {
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<RaceHost> {
+        Ok(RaceHost { listener: TcpListener::bind(address)? })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> { // handy with port 0, e.g. when racing over loopback
+        self.listener.local_addr()
+    }
+
+    pub fn start(self, players: usize, name: &str, secret: &str, mut on_join: impl FnMut(&str)) -> io::Result<Race> {
+        // waits until players - 1 others have joined, then sends everyone the secret and starts the race
+        let mut names = vec![clean_name(name)];
+        let mut connections: Vec<(TcpStream, BufReader<TcpStream>)> = Vec::new();
+        while names.len() < players {
+            let (stream, _) = self.listener.accept()?;
+            // something that connects and then says nothing would hold up everyone else, so it only gets so long
+            stream.set_read_timeout(Some(HELLO_TIMEOUT))?;
+            let mut reader = BufReader::new(stream.try_clone()?);
+            match read_line(&mut reader) {
+                Ok(Some(line)) if line.starts_with("HELLO ") => {
+                    stream.set_read_timeout(None)?; // no rush once they're in
+                    let name = clean_name(&line["HELLO ".len()..]);
+                    on_join(&name);
+                    names.push(name);
+                    connections.push((stream, reader));
+                },
+                _ => continue, // not one of ours (or gave up, or too slow), drop it and keep waiting
+            }
+        }
+
+        let mut streams: Vec<Option<TcpStream>> = vec![None];
+        for (index, (stream, _)) in connections.iter_mut().enumerate() {
+            writeln!(stream, "WELCOME {}", index + 1)?;
+            for (id, name) in names.iter().enumerate() {
+                writeln!(stream, "PLAYER {id} {name}")?;
+            }
+            writeln!(stream, "START {secret}")?;
+            streams.push(Some(stream.try_clone()?));
+        }
+
+        let shared = Arc::new(Mutex::new(Shared { players: names.iter().map(|n| Player::new(n)).collect(), generation: 0 }));
+        let streams = Arc::new(Mutex::new(streams));
+        for (index, (_, mut reader)) in connections.into_iter().enumerate() {
+            let id = index + 1;
+            let shared = Arc::clone(&shared);
+            let streams = Arc::clone(&streams);
+            thread::spawn(move || { // relay this player's progress to everyone else
+                while let Ok(Some(line)) = read_line(&mut reader) {
+                    let (kind, value) = line.split_once(' ').unwrap_or((&line, ""));
+                    if apply(&shared, id, kind, value) {
+                        broadcast(&streams, id, &format!("{kind} {id} {value}"));
+                    }
+                }
+                streams.lock().unwrap()[id] = None;
+                apply(&shared, id, "LEFT", "");
+                broadcast(&streams, id, &format!("LEFT {id}"));
+            });
+        }
+
+        Ok(Race { me: 0, shared, link: Link::Host(streams) })
+    }
+}
+
+fn apply(shared: &Mutex<Shared>, id: usize, kind: &str, value: &str) -> bool {
+    // updates a player from a message, returns whether it was valid (only valid messages get relayed)
+    let mut shared = shared.lock().unwrap();
+    let applied = match (shared.players.get_mut(id), kind) {
+        (Some(player), "ROW") if valid_pattern(value) && player.rows.len() < 6 => {
+            player.rows.push(value.to_string());
+            true
+        },
+        (Some(player), "DONE") if value == "1" || value == "0" => {
+            player.result = Some(value == "1");
+            true
+        },
+        (Some(player), "LEFT") => {
+            player.connected = false;
+            true
+        },
+        _ => false,
+    };
+    if kind == "LEFT" && id == 0 { // the host left, which takes everyone with it
+        for player in shared.players.iter_mut() {
+            player.connected = false;
+        }
+    }
+    if applied {
+        shared.generation += 1;
+    }
+    applied
+}
+
+fn broadcast(streams: &Mutex<Vec<Option<TcpStream>>>, from: usize, line: &str) {
+    let mut streams = streams.lock().unwrap();
+    for (id, stream) in streams.iter_mut().enumerate() {
+        if id == from {
+            continue;
+        }
+        if let Some(s) = stream {
+            if writeln!(s, "{line}").is_err() {
+                *stream = None; // their reader thread will notice and announce they left
+            }
+        }
+    }
+}
+
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    match reader.read_line(&mut line)? {
+        0 => Ok(None),
+        _ => Ok(Some(line.trim_end_matches(['\r', '\n']).to_string())),
+    }
+}
+
+fn valid_pattern(pattern: &str) -> bool {
+    pattern.len() == 5 && pattern.chars().all(|c| matches!(c, 'G' | 'Y' | '.'))
+}
+
+fn clean_name(name: &str) -> String {
+    // names go on one protocol line and in a narrow panel
+    let name: String = name.chars().filter(|c| !c.is_control()).take(12).collect();
+    match name.trim() {
+        "" => String::from("player"),
+        name => name.to_string(),
+    }
+}
+
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    fn wait_for(race: &Race, what: &str, check: impl Fn(&[Player]) -> bool) {
+        // messages arrive on other threads, so give them a moment
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !check(&race.opponents()) {
+            assert!(Instant::now() < deadline, "timed out waiting for {what}, have {:?}", race.opponents());
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn relays_over_loopback() {
+        let host = RaceHost::bind("127.0.0.1:0").unwrap();
+        let address = host.local_addr().unwrap();
+        let (joined, joins) = mpsc::channel();
+        let hosting = thread::spawn(move || host.start(3, "host", "CRANE", |name| joined.send(name.to_string()).unwrap()).unwrap());
+
+        // joining blocks until everyone's in, so each joiner gets a thread. One at a time, so ann is player 1 and bob 2
+        let ann = thread::spawn(move || Race::join(address, "ann").unwrap());
+        assert_eq!(joins.recv().unwrap(), "ann");
+        let bob = thread::spawn(move || Race::join(address, "bob").unwrap());
+        assert_eq!(joins.recv().unwrap(), "bob");
+        let mut host = hosting.join().unwrap();
+        let (mut ann, secret) = ann.join().unwrap();
+        assert_eq!(secret, "CRANE");
+        let (bob, secret) = bob.join().unwrap();
+        assert_eq!(secret, "CRANE");
+        let names: Vec<String> = bob.opponents().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, ["host", "ann"]);
+
+        // a joiner's rows and result go through the host to everyone else
+        ann.send_row("G.Y..");
+        ann.send_done(false);
+        for race in [&host, &bob] {
+            wait_for(race, "ann's row and result", |players| players.iter().any(|p| p.name == "ann" && p.rows == ["G.Y.."] && p.result == Some(false)));
+        }
+        // and the host's own go straight out
+        host.send_row("GGGGG");
+        host.send_done(true);
+        for race in [&ann, &bob] {
+            wait_for(race, "the host's row and result", |players| players[0].rows == ["GGGGG"] && players[0].result == Some(true));
+        }
+
+        // leaving gets announced too
+        drop(ann);
+        for race in [&host, &bob] {
+            wait_for(race, "ann to leave", |players| players.iter().any(|p| p.name == "ann" && !p.connected));
+        }
+        assert!(bob.opponents()[0].connected);
+    }
+}
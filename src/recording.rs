@@ -0,0 +1,256 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::input::{key_name, parse_key, Command, Key, KeyBindings};
+use crate::profile::{Profile, SavedGame};
+use crate::{config, KeyboardLayout, Mode};
+
+// recordings of whole games: every key press with when it happened, plus the messages and guesses the game produced
+// they're kept in recordings/ next to the game history (one file per game) and played back with "wordle replay",
+// which feeds the keys back through the Board at the same pace so everything gets drawn exactly as it was
+//
+// a file is a header, then "---", then one event per line, times in milliseconds from the start:
+//   wordle-recording 1
+//   mode = "normal"
+//   secret = "CRANE"
+//   hard = false
+//   theme = "dark"
+//   ...
+//   [keys]
+//   quit = "`"
+//   ...
+//   ---
+//   1520 key c
+//   2304 key enter
+//   2305 submit CRANX
+//   2306 msg Not in word list
+//   ...
+//   4410 submit CRANE
+//   4412 guess CRANE
+
+const RECORDINGS_DIR: &str = "recordings";
+const HEADER: &str = "wordle-recording 1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Key(Key),
+    Submit(String), // a word entered with Enter, whether or not it was accepted
+    Guess(String), // a guess that was accepted and went on the board
+    Message(String), // anything shown with print_msg, e.g. why a guess was rejected
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub at: u64, // milliseconds since the recording started
+    pub action: Action,
+}
+
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub mode: Mode,
+    pub secret: String,
+    pub hard: bool, // settings at the start, anything changed after that is in the keys
+    pub theme: String,
+    pub layout: KeyboardLayout,
+    pub bindings: KeyBindings, // what the recorded keys meant at the time
+    pub resumed: Option<SavedGame>, // the saved game that got picked up, if there was one
+    pub events: Vec<Event>,
+    started: Instant,
+}
+
+impl Recording {
+    pub fn new(mode: Mode, secret: &str, hard: bool, theme: &str, layout: KeyboardLayout, bindings: KeyBindings) -> Recording {
+        Recording { mode, secret: secret.to_string(), hard, theme: theme.to_string(), layout, bindings, resumed: None, events: Vec::new(), started: Instant::now() }
+    }
+
+    pub fn log(&mut self, action: Action) {
+        let at = self.started.elapsed().as_millis() as u64;
+        self.events.push(Event { at, action });
+    }
+
+    pub fn guesses(&self) -> Vec<&str> { // accepted guesses only
+        self.events.iter().filter_map(|e| match &e.action {
+            Action::Guess(word) => Some(word.as_str()),
+            _ => None,
+        }).collect()
+    }
+
+    pub fn result(&self) -> String { // for listing recordings
+        let guesses = self.guesses();
+        let secret = self.resumed.as_ref().map(|r| r.secret.as_str()).unwrap_or(&self.secret); // a resumed game brings its own word
+        match guesses.last() {
+            Some(last) if *last == secret => format!("won in {}", guesses.len()),
+            _ if guesses.len() >= 6 => String::from("lost"),
+            _ => String::from("unfinished"),
+        }
+    }
+
+    fn serialize(&self) -> String {
+        let (mode, day) = match self.mode {
+            Mode::Daily(day) => ("daily", Some(day)),
+            mode => (mode.name(), None),
+        };
+        let mut out = format!("{HEADER}\nmode = \"{mode}\"\nsecret = \"{}\"\nhard = {}\ntheme = \"{}\"\nlayout = \"{}\"\n",
+            self.secret, self.hard, self.theme, self.layout.name());
+        if let Some(day) = day {
+            out.push_str(&format!("day = {day}\n"));
+        }
+        if let Some(resumed) = &self.resumed {
+            out.push_str(&format!("resumed = \"{}\"\nresumed_secret = \"{}\"\nresumed_hard = {}\n", resumed.guesses.join(","), resumed.secret, resumed.hard));
+        }
+        out.push_str("[keys]\n"); // same as the config file's
+        for command in Command::ALL {
+            out.push_str(&format!("{} = \"{}\"\n", command.name(), self.bindings.names(command)));
+        }
+        out.push_str("---\n");
+        for event in &self.events {
+            let line = match &event.action {
+                Action::Key(key) => format!("key {}", key_name(key)),
+                Action::Submit(word) => format!("submit {word}"),
+                Action::Guess(word) => format!("guess {word}"),
+                Action::Message(message) => format!("msg {message}"),
+            };
+            out.push_str(&format!("{} {line}\n", event.at));
+        }
+        out
+    }
+
+    fn parse(text: &str) -> Result<Recording, String> {
+        let (header, events) = text.split_once("\n---\n").ok_or("not a recording")?;
+        let header = header.strip_prefix(HEADER).ok_or("not a recording, or from a newer version")?;
+        let values = config::parse_values(header)?;
+        let value = |key: &str| values.get(key).map(String::as_str);
+        let mut bindings = KeyBindings::default();
+        for command in Command::ALL {
+            bindings.set(command, value(&format!("keys.{}", command.name())).ok_or("no key bindings")?)?;
+        }
+        let mode = match (value("mode"), value("day").and_then(|d| d.parse().ok())) {
+            (Some("daily"), Some(day)) => Mode::Daily(day),
+            (Some("normal"), _) => Mode::Normal,
+            (Some("race"), _) => Mode::Race, // the other racers weren't recorded, so this plays back on its own
+            (Some("challenge"), _) => Mode::Challenge,
+            _ => return Err(String::from("unknown mode")),
+        };
+        let mut recording = Recording::new(
+            mode,
+            value("secret").ok_or("no secret word")?,
+            value("hard") == Some("true"),
+            value("theme").ok_or("no theme")?,
+            value("layout").and_then(KeyboardLayout::parse).unwrap_or_default(),
+            bindings,
+        );
+        if let (Some(guesses), Some(secret)) = (value("resumed"), value("resumed_secret")) {
+            recording.resumed = Some(SavedGame {
+                secret: secret.to_string(),
+                guesses: guesses.split(',').filter(|g| !g.is_empty()).map(String::from).collect(),
+                hard: value("resumed_hard") == Some("true"),
+                day: None,
+            });
+        }
+
+        for (number, line) in events.lines().enumerate() {
+            let bad_line = || format!("event {}: can't read \"{line}\"", number + 1);
+            let (at, rest) = line.split_once(' ').ok_or_else(bad_line)?;
+            let at: u64 = at.parse().map_err(|_| bad_line())?;
+            let action = match rest.split_once(' ').unwrap_or((rest, "")) {
+                ("key", name) => Action::Key(parse_key(name).ok_or_else(bad_line)?),
+                ("submit", word) => Action::Submit(word.to_string()),
+                ("guess", word) => Action::Guess(word.to_string()),
+                ("msg", message) => Action::Message(message.to_string()),
+                _ => return Err(bad_line()),
+            };
+            recording.events.push(Event { at, action });
+        }
+        Ok(recording)
+    }
+
+    pub fn play(&self, keys: Sender<Key>) {
+        // sends the recorded keys on their own thread, each at the time it was originally pressed
+        let events: Vec<(u64, Key)> = self.events.iter().filter_map(|e| match e.action {
+            Action::Key(key) => Some((e.at, key)),
+            _ => None,
+        }).collect();
+        thread::spawn(move || {
+            let start = Instant::now();
+            for (at, key) in events {
+                let due = Duration::from_millis(at);
+                if let Some(wait) = due.checked_sub(start.elapsed()) {
+                    thread::sleep(wait);
+                }
+                if keys.send(key).is_err() {
+                    break; // the board's gone
+                }
+            }
+        });
+    }
+}
+
+pub fn recordings_dir(profile: &Profile) -> PathBuf {
+    profile.dir().join(RECORDINGS_DIR)
+}
+
+pub fn save(profile: &Profile, recording: &Recording, timestamp: u64) -> io::Result<PathBuf> {
+    let dir = recordings_dir(profile);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{timestamp}-{}.txt", recording.mode.name()));
+    fs::write(&path, recording.serialize())?;
+    Ok(path)
+}
+
+pub fn list(profile: &Profile) -> Vec<PathBuf> {
+    // newest first. File names start with the time, so sorting the names sorts the games
+    let mut paths: Vec<PathBuf> = match fs::read_dir(recordings_dir(profile)) {
+        Ok(entries) => entries.flatten().map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "txt")).collect(),
+        Err(_) => Vec::new(), // nothing recorded yet
+    };
+    paths.sort_by_key(|p| std::cmp::Reverse(timestamp(p)));
+    paths
+}
+
+pub fn timestamp(path: &Path) -> u64 { // when the game the recording is of was saved
+    let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    name.split('-').next().and_then(|t| t.parse().ok()).unwrap_or(0)
+}
+
+pub fn load(path: &Path) -> Result<Recording, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    Recording::parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut bindings = KeyBindings::default();
+        bindings.set(Command::Settings, "1, f3").unwrap();
+        let mut recording = Recording::new(Mode::Daily(1234), "CRANE", true, "contrast", KeyboardLayout::Azerty, bindings);
+        recording.resumed = Some(SavedGame { secret: String::from("CRANE"), guesses: vec![String::from("SLATE")], hard: true, day: None });
+        recording.events = vec![
+            Event { at: 0, action: Action::Key(Key::Char('c')) },
+            Event { at: 15, action: Action::Key(Key::Char('\n')) },
+            Event { at: 16, action: Action::Submit(String::from("CRANX")) },
+            Event { at: 17, action: Action::Message(String::from("Not in word list")) },
+            Event { at: 900, action: Action::Key(Key::F(3)) },
+            Event { at: 1200, action: Action::Key(Key::Backspace) },
+            Event { at: 2000, action: Action::Guess(String::from("CRANE")) },
+        ];
+
+        let parsed = Recording::parse(&recording.serialize()).unwrap();
+        assert_eq!(parsed.mode, recording.mode);
+        assert_eq!(parsed.secret, recording.secret);
+        assert_eq!(parsed.hard, recording.hard);
+        assert_eq!(parsed.theme, recording.theme);
+        assert_eq!(parsed.layout, recording.layout);
+        for command in Command::ALL {
+            assert_eq!(parsed.bindings.keys(command), recording.bindings.keys(command));
+        }
+        assert_eq!(parsed.resumed, recording.resumed);
+        assert_eq!(parsed.events, recording.events);
+    }
+}
@@ -0,0 +1,261 @@
+use std::io::{stdout, Stdout, Write};
+
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::{AlternateScreen, IntoAlternateScreen};
+use termion::{clear, cursor, style};
+
+// where the board gets drawn. The Board only ever prints text at a position, clears and moves the cursor,
+// so anything that can do those can show the game: the terminal (TermionRenderer), or a grid in memory
+// (GridRenderer) that can be read back, e.g. to check what a screen looks like
+//
+// coloured text comes as spans, each a run of text and how it looks (see Theme::paint). Only TermionRenderer
+// turns those into escape codes. Positions are one-based columns and rows, same as the terminal
+
+// a colour, already brought down to what the terminal can show (see ColourSupport)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Colour {
+    Rgb(u8, u8, u8),
+    Ansi256(u8),
+    Ansi16(u8), // 0-7 the normal ones, 8-15 the bright ones
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Style {
+    pub fg: Option<Colour>,
+    pub bg: Option<Colour>,
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    pub fn reversed() -> Style {
+        Style { reverse: true, ..Style::default() }
+    }
+
+    pub fn underlined() -> Style {
+        Style { underline: true, ..Style::default() }
+    }
+
+    fn sgr(&self) -> String {
+        // the escape code that switches to this style, e.g. "\x1b[1;38;2;106;170;100m"
+        let mut codes = Vec::new();
+        for (on, code) in [(self.bold, "1"), (self.dim, "2"), (self.underline, "4"), (self.reverse, "7")] {
+            if on {
+                codes.push(code.to_string());
+            }
+        }
+        for (colour, base) in [(self.fg, 30), (self.bg, 40)] {
+            match colour {
+                Some(Colour::Rgb(r, g, b)) => codes.push(format!("{};2;{r};{g};{b}", base + 8)),
+                Some(Colour::Ansi256(n)) => codes.push(format!("{};5;{n}", base + 8)),
+                Some(Colour::Ansi16(n)) if n < 8 => codes.push((base + n as u16).to_string()),
+                Some(Colour::Ansi16(n)) => codes.push((base + 60 + n as u16 - 8).to_string()),
+                None => (),
+            }
+        }
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+// a run of text all in one style. A line of them is what gets printed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Span {
+    pub fn plain(text: impl Into<String>) -> Span {
+        Span { text: text.into(), style: Style::default() }
+    }
+
+    pub fn styled(text: impl Into<String>, style: Style) -> Span {
+        Span { text: text.into(), style }
+    }
+}
+
+pub trait Renderer {
+    fn size(&self) -> (u16, u16); // columns, rows
+    fn clear(&mut self); // the whole screen
+    fn clear_line(&mut self, row: u16);
+    fn print(&mut self, col: u16, row: u16, text: &str) {
+        self.print_spans(col, row, &[Span::plain(text)]);
+    }
+    fn print_spans(&mut self, col: u16, row: u16, spans: &[Span]);
+    fn move_cursor(&mut self, col: u16, row: u16);
+    fn show_cursor(&mut self, show: bool);
+    fn flush(&mut self);
+}
+
+// the real terminal, in raw mode on the alternate screen (both undone when it's dropped)
+pub struct TermionRenderer {
+    out: RawTerminal<AlternateScreen<Stdout>>,
+}
+
+impl TermionRenderer {
+    pub fn new() -> TermionRenderer {
+        TermionRenderer { out: stdout().into_alternate_screen().unwrap().into_raw_mode().unwrap() }
+    }
+}
+
+impl Default for TermionRenderer {
+    fn default() -> TermionRenderer {
+        TermionRenderer::new()
+    }
+}
+
+impl Renderer for TermionRenderer {
+    fn size(&self) -> (u16, u16) {
+        termion::terminal_size().unwrap_or((80, 24))
+    }
+
+    fn clear(&mut self) {
+        write!(self.out, "{}", clear::All).unwrap();
+    }
+
+    fn clear_line(&mut self, row: u16) {
+        write!(self.out, "{}{}", cursor::Goto(1, row.max(1)), clear::CurrentLine).unwrap();
+    }
+
+    fn print_spans(&mut self, col: u16, row: u16, spans: &[Span]) {
+        write!(self.out, "{}", cursor::Goto(col.max(1), row.max(1))).unwrap();
+        for span in spans {
+            match span.style == Style::default() {
+                true => write!(self.out, "{}", span.text).unwrap(),
+                false => write!(self.out, "{}{}{}", span.style.sgr(), span.text, style::Reset).unwrap(),
+            }
+        }
+    }
+
+    fn move_cursor(&mut self, col: u16, row: u16) {
+        write!(self.out, "{}", cursor::Goto(col.max(1), row.max(1))).unwrap();
+    }
+
+    fn show_cursor(&mut self, show: bool) {
+        match show {
+            true => write!(self.out, "{}", cursor::Show).unwrap(),
+            false => write!(self.out, "{}", cursor::Hide).unwrap(),
+        }
+    }
+
+    fn flush(&mut self) {
+        self.out.flush().unwrap();
+    }
+}
+
+// a screen in memory: one character per cell, plus the style it was printed in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell { ch: ' ', style: Style::default() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GridRenderer {
+    width: u16,
+    height: u16,
+    cells: Vec<Vec<Cell>>, // rows of cells
+    cursor: (u16, u16),
+    cursor_shown: bool,
+}
+
+impl GridRenderer {
+    pub fn new(width: u16, height: u16) -> GridRenderer {
+        GridRenderer {
+            width,
+            height,
+            cells: vec![vec![Cell::default(); width as usize]; height as usize],
+            cursor: (1, 1),
+            cursor_shown: true,
+        }
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) { // like dragging the window: what fits stays
+        for line in &mut self.cells {
+            line.resize(width as usize, Cell::default());
+        }
+        self.cells.resize(height as usize, vec![Cell::default(); width as usize]);
+        (self.width, self.height) = (width, height);
+    }
+
+    pub fn cell(&self, col: u16, row: u16) -> Option<&Cell> {
+        self.cells.get((row as usize).checked_sub(1)?)?.get((col as usize).checked_sub(1)?)
+    }
+
+    pub fn cursor(&self) -> Option<(u16, u16)> { // None while it's hidden
+        self.cursor_shown.then_some(self.cursor)
+    }
+
+    pub fn text(&self) -> String {
+        // the characters on screen, without any styling and with trailing spaces trimmed
+        let lines: Vec<String> = self.cells.iter().map(|line| line.iter().map(|c| c.ch).collect::<String>().trim_end().to_string()).collect();
+        let mut text = lines.join("\n");
+        text.truncate(text.trim_end().len());
+        text
+    }
+}
+
+impl Renderer for GridRenderer {
+    fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    fn clear(&mut self) {
+        for line in &mut self.cells {
+            line.fill(Cell::default());
+        }
+    }
+
+    fn clear_line(&mut self, row: u16) {
+        if let Some(line) = self.cells.get_mut((row as usize).wrapping_sub(1)) {
+            line.fill(Cell::default());
+        }
+    }
+
+    fn print_spans(&mut self, col: u16, row: u16, spans: &[Span]) {
+        // anything off the edge is lost, like on a terminal with wrapping turned off
+        let mut col = col.max(1);
+        for span in spans {
+            for ch in span.text.chars() {
+                if let Some(cell) = self.cells.get_mut((row as usize).wrapping_sub(1)).and_then(|line| line.get_mut(col as usize - 1)) {
+                    *cell = Cell { ch, style: span.style };
+                }
+                col += 1;
+            }
+        }
+        self.cursor = (col, row);
+    }
+
+    fn move_cursor(&mut self, col: u16, row: u16) {
+        self.cursor = (col, row);
+    }
+
+    fn show_cursor(&mut self, show: bool) {
+        self.cursor_shown = show;
+    }
+
+    fn flush(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_codes() {
+        // what the terminal gets sent for a few of the styles the game uses
+        let tile = Style { fg: Some(Colour::Ansi16(15)), bg: Some(Colour::Rgb(106, 170, 100)), bold: true, ..Style::default() };
+        assert_eq!(tile.sgr(), "\x1b[1;97;48;2;106;170;100m");
+        assert_eq!(Style { fg: Some(Colour::Ansi256(71)), ..Style::default() }.sgr(), "\x1b[38;5;71m");
+        assert_eq!(Style { bg: Some(Colour::Ansi16(2)), ..Style::reversed() }.sgr(), "\x1b[7;42m");
+        assert_eq!(Style::underlined().sgr(), "\x1b[4m");
+    }
+}
@@ -0,0 +1,567 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::history::{self, GameRecord};
+use crate::profile::Profile;
+
+// stats persistence
+// stats live in the XDG data directory (~/.local/share/wordle by default) instead of wherever the game was launched from
+// the file is never edited in place: a new copy is written to a temp file and renamed over the old one,
+// and a lock file stops two games finishing at the same time from clobbering each other
+// each mode gets its own line of numbers, keyed like "normal" or "normal-hard"
+// every profile (see profile.rs) has its own stats file in its own directory
+
+const STATS_FILE: &str = "wordle_stats.txt";
+const LOCK_FILE: &str = "wordle_stats.lock";
+const LEGACY_FILE: &str = "./wordle_stats.txt"; // where versions before this one saved stats
+const HEADER: &str = "wordle-stats 2"; // first line of the file, so a truncated or foreign file can't be mistaken for stats
+const DEFAULT_KEY: &str = "normal"; // older files didn't know about modes, so their stats land here
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub wins: [u16; 6], // wins on turns 1-6
+    pub failures: u16,
+    pub streak: u16, // current streak
+    pub max_streak: u16,
+    pub last_day: Option<i64>, // daily modes only: the day (see daily.rs) of the last puzzle played, for calendar streaks
+}
+
+impl Stats {
+    pub fn record(&mut self, win: bool, turn: usize) {
+        self.count(win, turn);
+        self.streak = if win { self.streak + 1 } else { 0 };
+        self.max_streak = self.max_streak.max(self.streak);
+    }
+
+    pub fn record_daily(&mut self, win: bool, turn: usize, day: i64) {
+        // daily streaks count calendar days rather than games: a day only counts once, and a missed day resets the streak
+        self.count(win, turn); // the totals count every game
+        self.streak = match self.last_day {
+            Some(last_day) if last_day == day => self.streak, // already played today
+            _ if !win => 0,
+            Some(last_day) if last_day == day - 1 => self.streak + 1, // played yesterday, streak continues
+            _ => 1, // first daily, or days were missed
+        };
+        self.max_streak = self.max_streak.max(self.streak);
+        self.last_day = Some(day);
+    }
+
+    fn count(&mut self, win: bool, turn: usize) { // the totals and the distribution, leaving streaks to the callers
+        match win {
+            true => if let Some(count) = self.wins.get_mut(turn.wrapping_sub(1)) {
+                *count += 1; // increase wins associated with turn number
+            },
+            false => self.failures += 1,
+        }
+    }
+
+    pub fn expire_streak(&mut self, today: i64) {
+        // a daily streak survives until the end of the day after the last win, then it's gone
+        if let Some(last_day) = self.last_day {
+            if last_day < today - 1 {
+                self.streak = 0;
+            }
+        }
+    }
+
+    pub fn played(&self) -> u16 {
+        self.won() + self.failures
+    }
+
+    pub fn won(&self) -> u16 {
+        self.wins.iter().sum()
+    }
+
+    pub fn win_percentage(&self) -> u16 {
+        match self.played() {
+            0 => 0, // nothing played yet (e.g. a tab for a mode you've never finished a game in)
+            played => (self.won() as u32 * 100 / played as u32) as u16,
+        }
+    }
+
+    pub fn average_guesses(&self) -> Option<f64> {
+        // over wins only, since a failure doesn't have a guess count
+        match self.won() {
+            0 => None,
+            won => Some(self.wins.iter().enumerate().map(|(i, n)| (i as u32 + 1) * *n as u32).sum::<u32>() as f64 / won as f64),
+        }
+    }
+
+    fn to_numbers(self) -> [u16; 9] { // same order as the original file: 1s to 6s, failures, current streak, max streak
+        let mut numbers = [0; 9];
+        numbers[..6].copy_from_slice(&self.wins);
+        numbers[6] = self.failures;
+        numbers[7] = self.streak;
+        numbers[8] = self.max_streak;
+        numbers
+    }
+
+    fn from_numbers(numbers: &[u16]) -> Option<Stats> {
+        if numbers.len() != 9 {
+            return None;
+        }
+        let mut wins = [0; 6];
+        wins.copy_from_slice(&numbers[..6]);
+        Some(Stats { wins, failures: numbers[6], streak: numbers[7], max_streak: numbers[8], last_day: None })
+    }
+
+    fn parse_line(line: &str) -> Option<Stats> {
+        // nine numbers, then the last day played for daily modes
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let numbers: Vec<u16> = fields.iter().take(9).map(|n| n.parse().ok()).collect::<Option<_>>()?;
+        let mut stats = Stats::from_numbers(&numbers)?;
+        match fields.get(9..)? {
+            [] => (),
+            [day] => stats.last_day = Some(day.parse().ok()?),
+            _ => return None,
+        }
+        Some(stats)
+    }
+
+    fn parse_legacy(text: &str) -> Option<Stats> {
+        // old files were never truncated, so there can be stale junk after the first nine lines. Ignore it like the old reader did
+        let numbers: Vec<u16> = text.lines().take(9).map(|line| line.trim().parse().ok()).collect::<Option<_>>()?;
+        Stats::from_numbers(&numbers)
+    }
+
+    fn serialize(&self) -> String {
+        let mut out = self.to_numbers().iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" ");
+        if let Some(day) = self.last_day {
+            out.push_str(&format!(" {day}"));
+        }
+        out
+    }
+}
+
+pub fn key(mode: &str, hard: bool) -> String {
+    if hard {
+        format!("{mode}-hard")
+    } else {
+        mode.to_string()
+    }
+}
+
+pub fn label(key: &str) -> String {
+    // "normal-hard" -> "Normal*", using the asterisk NYT uses for hard mode results
+    let (mode, hard) = match key.strip_suffix("-hard") {
+        Some(mode) => (mode, true),
+        None => (key, false),
+    };
+    let mut label: String = mode.split('-').map(|word| {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+            None => String::new(),
+        }
+    }).collect::<Vec<_>>().join(" ");
+    if hard {
+        label.push('*');
+    }
+    label
+}
+
+pub fn summary(key: &str, stats: &Stats, insights: &history::Insights) -> String {
+    // plain-text version of the stats screen, for printing outside the game (e.g. "wordle stats")
+    let average = match stats.average_guesses() {
+        Some(average) => format!("{average:.1}"),
+        None => String::from("-"),
+    };
+    let mut out = format!("{}\n{} Played   {} Win %   {average} Avg Guesses   {} Current Streak   {} Max Streak\n\n",
+        label(key),
+        stats.played(),
+        stats.win_percentage(),
+        stats.streak,
+        stats.max_streak,
+    );
+
+    let big_bar = stats.wins.iter().copied().chain([stats.failures]).max().unwrap_or(0);
+    let rows = stats.wins.iter().enumerate().map(|(i, n)| ((i + 1).to_string(), *n)).chain([(String::from("X"), stats.failures)]);
+    for (name, count) in rows {
+        let ticks = if big_bar == 0 { 0 } else { count as usize * 40 / big_bar as usize };
+        out.push_str(&format!("| {name} |{} {count}\n", "|".repeat(ticks)));
+    }
+
+    if !insights.trend.is_empty() {
+        out.push_str(&format!("\nTrend    {}\n", history::sparkline(&insights.trend)));
+    }
+    if !insights.openers.is_empty() {
+        out.push_str(&format!("Openers  {}\n", format_openers(&insights.openers)));
+    }
+    if !insights.hardest.is_empty() {
+        out.push_str(&format!("Hardest  {}\n", insights.hardest.join(", ")));
+    }
+    out
+}
+
+pub fn spoken_summary(key: &str, stats: &Stats) -> String {
+    // the summary in sentences, for screen readers (the bars and columns above read out as noise)
+    let average = match stats.average_guesses() {
+        Some(average) => format!("{average:.1} guesses on average"),
+        None => String::from("no wins to average yet"),
+    };
+    let wins: Vec<String> = stats.wins.iter().enumerate().map(|(i, n)| format!("{n} in {}", i + 1)).collect();
+    format!("{} stats: {} played, {} percent won, {average}, current streak {}, max streak {}.\nWins by number of guesses: {}. Failed: {}.\n",
+        label(key).replace('*', " hard mode"),
+        stats.played(),
+        stats.win_percentage(),
+        stats.streak,
+        stats.max_streak,
+        wins.join(", "),
+        stats.failures,
+    )
+}
+
+pub fn format_openers(openers: &[history::Opener]) -> String {
+    // "CRANE 4 (75%)  SLATE 2 (50%)"
+    openers.iter().map(|o| format!("{} {} ({}%)", o.word, o.played, o.won * 100 / o.played)).collect::<Vec<_>>().join("  ")
+}
+
+// every mode's stats, as stored in the stats file
+#[derive(Debug, Clone, Default)]
+pub struct StatsBook {
+    modes: BTreeMap<String, Stats>,
+}
+
+impl StatsBook {
+    pub fn get(&self, key: &str) -> Stats {
+        self.modes.get(key).copied().unwrap_or_default()
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> &mut Stats {
+        self.modes.entry(key.to_string()).or_default()
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.modes.keys().cloned().collect()
+    }
+
+    pub fn expire_streaks(&mut self, today: i64) {
+        for stats in self.modes.values_mut() {
+            stats.expire_streak(today);
+        }
+    }
+
+    fn parse(text: &str) -> Option<StatsBook> {
+        // strict: the header, then "key n n n n n n n n n" lines. Anything else counts as corrupted
+        let mut lines = text.lines();
+        let header = lines.next()?;
+        if header != HEADER {
+            return None;
+        }
+        let mut book = StatsBook::default();
+        for line in lines {
+            let (key, numbers) = line.split_once(' ')?;
+            if key.is_empty() || book.modes.insert(key.to_string(), Stats::parse_line(numbers)?).is_some() {
+                return None; // nameless or duplicated modes mean something's gone wrong
+            }
+        }
+        Some(book)
+    }
+
+    fn serialize(&self) -> String {
+        let mut out = format!("{HEADER}\n");
+        for (key, stats) in &self.modes {
+            out.push_str(&format!("{key} {}\n", stats.serialize()));
+        }
+        out
+    }
+}
+
+// result of recording a game
+pub struct Saved {
+    pub book: StatsBook,
+    pub backup: Option<PathBuf>, // where a corrupted stats file was moved to, if there was one
+    pub history_error: Option<io::Error>, // the stats saved but the game couldn't be added to the history
+}
+
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new(); // stats_dir from the config file, if it's set
+
+pub fn set_data_dir(dir: PathBuf) { // before anything's read or saved
+    let _ = DATA_DIR.set(dir);
+}
+
+pub fn data_dir() -> PathBuf {
+    // wherever the config file says, otherwise
+    // $XDG_DATA_HOME/wordle, falling back to ~/.local/share/wordle, then to the working directory if there's no home
+    if let Some(dir) = DATA_DIR.get() {
+        return dir.clone();
+    }
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir).join("wordle");
+    }
+    match std::env::var_os("HOME").filter(|h| !h.is_empty()) {
+        Some(home) => PathBuf::from(home).join(".local/share/wordle"),
+        None => PathBuf::from("."),
+    }
+}
+
+pub fn stats_path(profile: &Profile) -> PathBuf {
+    profile.dir().join(STATS_FILE)
+}
+
+pub fn load(profile: &Profile) -> io::Result<StatsBook> {
+    // read-only look at the stats, for when recording fails but there's still something to show
+    Ok(read(&stats_path(profile), false, legacy_path(profile))?.0)
+}
+
+pub fn record(profile: &Profile, game: &GameRecord, day: Option<i64>) -> io::Result<Saved> {
+    // day is only given for daily games, whose streaks follow the calendar
+    let dir = profile.dir();
+    fs::create_dir_all(&dir)?;
+    let _lock = Lock::acquire(dir.join(LOCK_FILE))?; // held until the new file is in place
+
+    let path = dir.join(STATS_FILE);
+    let (mut book, backup) = read(&path, true, legacy_path(profile))?;
+    match day {
+        Some(day) => book.get_mut(&game.key).record_daily(game.win, game.turns(), day),
+        None => book.get_mut(&game.key).record(game.win, game.turns()),
+    }
+    write_atomic(&path, &book.serialize())?;
+    // totals first: they're what the stats screen shows, the history is extra detail. So failing to add to it
+    // only gets a warning, the game has counted and saving again would count it twice
+    let history_error = history::append(profile, game).err();
+
+    Ok(Saved { book, backup, history_error })
+}
+
+fn legacy_path(profile: &Profile) -> Option<&'static Path> {
+    // only the default profile picks up old stats, they were from before there were profiles
+    profile.is_default().then_some(Path::new(LEGACY_FILE))
+}
+
+fn read(path: &Path, back_up_corrupt: bool, legacy: Option<&Path>) -> io::Result<(StatsBook, Option<PathBuf>)> {
+    match fs::read_to_string(path) {
+        Ok(text) => match StatsBook::parse(&text) {
+            Some(book) => Ok((book, None)),
+            None if back_up_corrupt => {
+                // keep the damaged file around for inspection rather than silently starting over
+                let backup = backup_path(path);
+                fs::rename(path, &backup)?;
+                Ok((StatsBook::default(), Some(backup)))
+            },
+            None => Ok((StatsBook::default(), None)),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            // first run with this version: pick up the stats an older version left in the working directory, if there are any to look for
+            let mut book = StatsBook::default();
+            let Some(legacy) = legacy else {
+                return Ok((book, None));
+            };
+            if let Some(legacy) = fs::read_to_string(legacy).ok().and_then(|text| Stats::parse_legacy(&text)) {
+                book.modes.insert(DEFAULT_KEY.to_string(), legacy);
+            }
+            Ok((book, None))
+        },
+        Err(e) => Err(e), // unreadable (permissions etc.) - don't overwrite something we couldn't read
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".corrupt-{}", history::now_secs()));
+    path.with_file_name(name)
+}
+
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    // write everything to a temp file in the same directory, sync it, then rename it over the real one
+    // a crash at any point leaves either the old file or the new one, never half of each
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut file = OpenOptions::new().write(true).create(true).truncate(true).open(&temp)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp); // don't leave temp files lying around
+    }
+    result?;
+
+    // make the rename itself durable (only possible on unix, where a directory can be opened and synced)
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+// lock file held while stats are read, updated and written back
+// created with create_new so only one game can hold it; removed when dropped
+struct Lock {
+    path: PathBuf,
+}
+
+impl Lock {
+    fn acquire(path: PathBuf) -> io::Result<Lock> {
+        let start = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id()); // just for anyone looking at a stuck lock
+                    return Ok(Lock { path });
+                },
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if Lock::is_stale(&path) { // left behind by a game that crashed mid-save
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if start.elapsed() > Duration::from_secs(3) {
+                        return Err(io::Error::new(io::ErrorKind::WouldBlock, "stats are locked by another game"));
+                    }
+                    std::thread::sleep(Duration::from_millis(50));
+                },
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn is_stale(path: &Path) -> bool {
+        // a save takes milliseconds, so a lock older than this is from a game that died
+        match fs::metadata(path).and_then(|m| m.modified()) {
+            Ok(modified) => modified.elapsed().map(|age| age > Duration::from_secs(30)).unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(streak: u16, max_streak: u16, last_day: i64) -> Stats {
+        Stats { streak, max_streak, last_day: Some(last_day), ..Stats::default() }
+    }
+
+    #[test]
+    fn daily_streak_same_day() {
+        let mut stats = daily(3, 3, 9);
+        stats.record_daily(true, 2, 9);
+        assert_eq!((stats.streak, stats.max_streak, stats.wins[1]), (3, 3, 1));
+    }
+
+    #[test]
+    fn daily_streak_next_day() {
+        let mut stats = daily(3, 3, 9);
+        stats.record_daily(true, 4, 10);
+        assert_eq!((stats.streak, stats.max_streak, stats.wins[3], stats.last_day), (4, 4, 1, Some(10)));
+    }
+
+    #[test]
+    fn daily_streak_after_a_gap() {
+        let mut stats = daily(3, 3, 6);
+        stats.record_daily(true, 3, 10);
+        assert_eq!((stats.streak, stats.max_streak, stats.wins[2]), (1, 3, 1));
+    }
+
+    #[test]
+    fn daily_streak_loss() {
+        let mut stats = daily(3, 5, 9);
+        stats.record_daily(false, 6, 10);
+        assert_eq!((stats.streak, stats.max_streak, stats.failures, stats.played()), (0, 5, 1, 1));
+    }
+
+    fn temp_dir(name: &str) -> PathBuf { // a fresh directory for one test
+        let dir = std::env::temp_dir().join(format!("wordle-stats-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let mut book = StatsBook::default();
+        book.get_mut("normal").record(true, 3);
+        book.get_mut("daily-hard").record_daily(false, 6, 100);
+        let parsed = StatsBook::parse(&book.serialize()).unwrap();
+        assert_eq!(parsed.keys(), ["daily-hard", "normal"]);
+        assert_eq!(parsed.get("normal"), book.get("normal"));
+        assert_eq!(parsed.get("daily-hard"), book.get("daily-hard"));
+    }
+
+    #[test]
+    fn parse_rejects_damage() {
+        assert!(StatsBook::parse("wordle-stats 1\n1\n2\n3\n4\n5\n6\n7\n8\n9\n").is_none()); // never released
+        assert!(StatsBook::parse("wordle-stats 2\nnormal 1 2 3\n").is_none());
+        assert!(StatsBook::parse("wordle-stats 2\nnormal 0 0 0 0 0 0 0 0 0\nnormal 0 0 0 0 0 0 0 0 0\n").is_none());
+        assert!(StatsBook::parse("").is_none());
+    }
+
+    #[test]
+    fn corrupt_file_is_backed_up() {
+        let dir = temp_dir("corrupt");
+        let path = dir.join(STATS_FILE);
+        fs::write(&path, "not stats").unwrap();
+
+        // just looking leaves it alone
+        let (book, backup) = read(&path, false, None).unwrap();
+        assert!(book.keys().is_empty() && backup.is_none());
+        assert!(path.exists());
+
+        // recording moves it aside and starts again
+        let (book, backup) = read(&path, true, None).unwrap();
+        assert!(book.keys().is_empty());
+        let backup = backup.unwrap();
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(backup).unwrap(), "not stats");
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn legacy_stats_are_picked_up() {
+        let dir = temp_dir("legacy");
+        let legacy = dir.join("old_stats.txt");
+        fs::write(&legacy, "1\n2\n3\n0\n0\n0\n4\n2\n5\nstale junk\n").unwrap();
+
+        let (book, _) = read(&dir.join(STATS_FILE), true, Some(&legacy)).unwrap();
+        let stats = book.get(DEFAULT_KEY);
+        assert_eq!((stats.wins, stats.failures, stats.streak, stats.max_streak), ([1, 2, 3, 0, 0, 0], 4, 2, 5));
+        // only the default profile looks for them
+        let (book, _) = read(&dir.join(STATS_FILE), true, None).unwrap();
+        assert!(book.keys().is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn lock_is_exclusive() {
+        let dir = temp_dir("lock");
+        let path = dir.join(LOCK_FILE);
+        let lock = Lock::acquire(path.clone()).unwrap();
+        assert_eq!(Lock::acquire(path.clone()).err().map(|e| e.kind()), Some(io::ErrorKind::WouldBlock));
+        drop(lock);
+        assert!(!path.exists());
+        drop(Lock::acquire(path.clone()).unwrap());
+
+        // one left behind by a game that died gets taken over
+        let file = fs::File::create(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() - Duration::from_secs(60)).unwrap();
+        assert!(Lock::acquire(path).is_ok());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_file() {
+        let dir = temp_dir("write");
+        let path = dir.join(STATS_FILE);
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        let files: Vec<_> = fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(files, [STATS_FILE]); // no temp files left over
+        let _ = fs::remove_dir_all(dir);
+    }
+}
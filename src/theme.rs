@@ -0,0 +1,243 @@
+use crate::render::{Colour, Span, Style};
+use crate::Letter;
+
+// colour themes for the tiles, keyboard, race panel and stats bars
+// a theme is just three colours: right spot (green in the original), wrong spot (yellow) and not in the word (grey)
+// colours are given as RGB and brought down to whatever the terminal can show (see ColourSupport)
+//
+// themes of your own go in the config file:
+//   theme = "mine"            # the default for profiles that haven't picked one
+//   [themes.mine]
+//   correct = "#6aaa64"
+//   present = "#c9b458"
+//   absent = "#787c7e"
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub fn parse(text: &str) -> Option<Rgb> {
+        // "#6aaa64" or "106, 170, 100"
+        let text = text.trim();
+        if let Some(hex) = text.strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return None;
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            return Some(Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        let channels: Vec<u8> = text.split(',').map(|c| c.trim().parse().ok()).collect::<Option<_>>()?;
+        match channels[..] {
+            [r, g, b] => Some(Rgb(r, g, b)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub correct: Rgb,
+    pub present: Rgb,
+    pub absent: Rgb,
+}
+
+impl Theme {
+    fn new(name: &str, correct: Rgb, present: Rgb, absent: Rgb) -> Theme {
+        Theme { name: name.to_string(), correct, present, absent }
+    }
+
+    pub fn builtin() -> Vec<Theme> {
+        vec![
+            Theme::new("dark", Rgb(106, 170, 100), Rgb(201, 180, 88), Rgb(120, 124, 126)), // for dark backgrounds (the default)
+            Theme::new("light", Rgb(60, 120, 55), Rgb(160, 130, 20), Rgb(170, 170, 170)), // darker colours that show up on white
+            Theme::new("contrast", Rgb(245, 121, 58), Rgb(133, 192, 249), Rgb(120, 124, 126)), // orange and blue, like the original's high contrast mode
+            // colourblind palettes, from the Okabe-Ito set
+            Theme::new("deuteranopia", Rgb(0, 114, 178), Rgb(230, 159, 0), Rgb(120, 124, 126)), // red-green (most common)
+            Theme::new("protanopia", Rgb(86, 180, 233), Rgb(240, 228, 66), Rgb(120, 124, 126)), // red-green, reds look dark
+            Theme::new("tritanopia", Rgb(0, 158, 115), Rgb(213, 94, 0), Rgb(120, 124, 126)), // blue-yellow
+        ]
+    }
+
+    fn colour(&self, letter: Letter) -> Rgb {
+        match letter {
+            Letter::Green => self.correct,
+            Letter::Yellow => self.present,
+            Letter::Grey => self.absent,
+        }
+    }
+
+    pub(crate) fn paint(&self, text: &str, letter: Letter, support: ColourSupport) -> Span {
+        // text in the theme's colour for a letter, as well as the terminal can manage
+        let style = match support.colour(self.colour(letter)) {
+            Some(colour) => Style { fg: Some(colour), ..Style::default() },
+            None => match letter { // no colour at all (NO_COLOR), so the letters have to be told apart by style instead
+                Letter::Green => Style { bold: true, ..Style::default() },
+                Letter::Yellow => Style::underlined(),
+                Letter::Grey => Style { dim: true, ..Style::default() },
+            },
+        };
+        Span::styled(text, style)
+    }
+
+    pub(crate) fn fill(&self, text: &str, letter: Letter, support: ColourSupport) -> Span {
+        // text on a background of the theme's colour (for tiles), in black or white, whichever stands out more
+        let rgb = self.colour(letter);
+        let Rgb(r, g, b) = rgb;
+        let ink = if (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000 > 180 { 0 } else { 15 };
+        let style = match support.colour(rgb) {
+            Some(colour) => Style { fg: Some(Colour::Ansi16(ink)), bg: Some(colour), bold: true, ..Style::default() },
+            None => match letter { // reversed tiles instead, styled like paint's letters
+                Letter::Green => Style { bold: true, reverse: true, ..Style::default() },
+                Letter::Yellow => Style { underline: true, reverse: true, ..Style::default() },
+                Letter::Grey => Style { dim: true, ..Style::default() },
+            },
+        };
+        Span::styled(text, style)
+    }
+}
+
+// every theme on offer: the built-in ones plus any from the config file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Themes {
+    pub default: String, // used when a profile hasn't picked one
+    pub custom: Vec<Theme>,
+}
+
+impl Default for Themes {
+    fn default() -> Themes {
+        Themes { default: String::from("dark"), custom: Vec::new() }
+    }
+}
+
+impl Themes {
+    pub fn all(&self) -> Vec<Theme> {
+        let mut themes = Theme::builtin();
+        for theme in &self.custom {
+            match themes.iter_mut().find(|t| t.name == theme.name) {
+                Some(existing) => *existing = theme.clone(), // the config can redefine a built-in one
+                None => themes.push(theme.clone()),
+            }
+        }
+        themes
+    }
+
+    pub fn get(&self, name: &str) -> Option<Theme> {
+        self.all().into_iter().find(|t| t.name == name)
+    }
+
+    pub fn get_or_default(&self, name: &str) -> Theme {
+        self.get(name).or_else(|| self.get(&self.default)).unwrap_or_else(|| Theme::builtin().remove(0))
+    }
+
+    pub fn next(&self, current: &str) -> Theme { // for cycling through them in the game
+        let all = self.all();
+        let index = all.iter().position(|t| t.name == current).map(|i| i + 1).unwrap_or(0);
+        all[index % all.len()].clone()
+    }
+}
+
+// how much colour the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColourSupport {
+    TrueColor, // 24-bit
+    Ansi256,
+    Ansi16,
+    None,
+}
+
+impl ColourSupport {
+    pub fn detect() -> ColourSupport {
+        // NO_COLOR (https://no-color.org) wins, then COLORTERM, then TERM. Assumes the basic 16 if nothing says otherwise
+        let var = |name: &str| std::env::var(name).unwrap_or_default().to_lowercase();
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return ColourSupport::None;
+        }
+        let (colorterm, term) = (var("COLORTERM"), var("TERM"));
+        if colorterm == "truecolor" || colorterm == "24bit" || term.contains("truecolor") || term.contains("direct") {
+            ColourSupport::TrueColor
+        } else if term.contains("256color") {
+            ColourSupport::Ansi256
+        } else if term == "dumb" {
+            ColourSupport::None
+        } else {
+            ColourSupport::Ansi16
+        }
+    }
+
+    pub fn colour(&self, rgb: Rgb) -> Option<Colour> { // None if it can't show any
+        match self {
+            ColourSupport::TrueColor => Some(Colour::Rgb(rgb.0, rgb.1, rgb.2)),
+            ColourSupport::Ansi256 => Some(Colour::Ansi256(ansi_256(rgb))),
+            ColourSupport::Ansi16 => Some(Colour::Ansi16(ansi_16(rgb))),
+            ColourSupport::None => None,
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Option<ColourSupport>> {
+        // for the config file: Some(None) means work it out (auto)
+        match text.trim().to_lowercase().as_str() {
+            "auto" => Some(None),
+            "truecolor" | "24bit" => Some(Some(ColourSupport::TrueColor)),
+            "256" => Some(Some(ColourSupport::Ansi256)),
+            "16" => Some(Some(ColourSupport::Ansi16)),
+            "none" => Some(Some(ColourSupport::None)),
+            _ => None,
+        }
+    }
+}
+
+fn ansi_256(Rgb(r, g, b): Rgb) -> u8 {
+    // nearest of the 6x6x6 colour cube and the 24 greys
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest_level = |c: u8| (0..6).min_by_key(|i| (LEVELS[*i] as i32 - c as i32).abs()).unwrap_or(0);
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = Rgb(LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let grey_index = (average.saturating_sub(3) / 10).min(23) as u8; // greys run 8, 18, ... 238
+    let grey_level = 8 + grey_index * 10;
+    let grey = Rgb(grey_level, grey_level, grey_level);
+
+    // only near-greys go on the grey ramp, even when it's closer: muted colours should stay colours
+    let chroma = r.max(g).max(b) - r.min(g).min(b);
+    if chroma < 24 && distance(Rgb(r, g, b), grey) < distance(Rgb(r, g, b), cube) {
+        232 + grey_index
+    } else {
+        16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8
+    }
+}
+
+fn ansi_16(Rgb(r, g, b): Rgb) -> u8 {
+    // the basic colours (0-7 normal, 8-15 bright), picked by hue
+    // (nearest by distance turns the softer theme colours grey, which defeats the point)
+    let (max, min) = (r.max(g).max(b) as i32, r.min(g).min(b) as i32);
+    if max - min < 40 { // not much colour to it: a grey
+        return match max {
+            0..=63 => 0,
+            64..=159 => 8,
+            160..=219 => 7,
+            _ => 15,
+        };
+    }
+    let (r, g, b) = (r as i32, g as i32, b as i32);
+    let hue = match max {
+        _ if max == r => (60 * (g - b) / (max - min) + 360) % 360,
+        _ if max == g => 60 * (b - r) / (max - min) + 120,
+        _ => 60 * (r - g) / (max - min) + 240,
+    };
+    let basic = match (hue + 30) / 60 % 6 { // red, yellow, green, cyan, blue, magenta
+        0 => 1,
+        1 => 3,
+        2 => 2,
+        3 => 6,
+        4 => 4,
+        _ => 5,
+    };
+    if max < 160 { basic } else { 8 + basic }
+}
+
+fn distance(Rgb(r1, g1, b1): Rgb, Rgb(r2, g2, b2): Rgb) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
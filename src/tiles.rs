@@ -0,0 +1,135 @@
+use crate::layout::WORD_LENGTH;
+use crate::render::{Span, Style};
+use crate::theme::{ColourSupport, Theme};
+use crate::Letter;
+
+// what the board's tiles look like. Ascii is the original: coloured letters between | and --- lines,
+// and works anywhere. Tiles is closer to the real game: letters on solid coloured tiles inside box-drawing borders,
+// and bigger tiles (three rows tall, one box each) when the terminal has room for them (see Layout)
+//   ascii                    tiles                    large tiles
+//   ---------------------    ┌───┬───┬───┬───┬───┐    ╭───╮ ╭───╮ ╭───╮
+//   | C | R | A | N | E |    │ C │ R │ A │ N │ E │    │ C │ │ R │ │ A │ ...
+//   ---------------------    ├───┼───┼───┼───┼───┤    ╰───╯ ╰───╯ ╰───╯
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BoardStyle {
+    #[default]
+    Ascii,
+    Tiles,
+}
+
+impl BoardStyle {
+    const ALL: [BoardStyle; 2] = [BoardStyle::Ascii, BoardStyle::Tiles];
+
+    pub fn name(&self) -> &'static str { // saved in profile preferences
+        match self {
+            BoardStyle::Ascii => "ascii",
+            BoardStyle::Tiles => "tiles",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<BoardStyle> {
+        BoardStyle::ALL.into_iter().find(|s| s.name() == text.trim().to_lowercase())
+    }
+
+    pub fn next(&self) -> BoardStyle { // for cycling through them in the settings menu
+        let index = BoardStyle::ALL.iter().position(|s| s == self).unwrap_or(0);
+        BoardStyle::ALL[(index + 1) % BoardStyle::ALL.len()]
+    }
+
+    pub fn large_tiles(&self) -> bool { // whether this style wants three-row tiles, if they fit
+        *self == BoardStyle::Tiles
+    }
+}
+
+// the lines around and between rows of one-row tiles (large tiles have a box each instead)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edge {
+    Top,
+    Between,
+    Bottom,
+}
+
+// what a tile is showing. Half and Edge are a tile part way through turning over (see animation.rs):
+// squashed to half height (only different for large tiles) and side on
+#[derive(Clone, Copy)]
+pub(crate) enum Face {
+    Plain, // the letter, not scored yet
+    Flagged, // not scored yet either, but picked out (reversed) as the reason a guess wasn't allowed
+    Half(Option<Letter>), // None on the way down, the colour on the way back up
+    Edge,
+    Scored(Letter),
+}
+
+pub(crate) fn tile(style: BoardStyle, large: bool, letter: char, face: Face, theme: &Theme, support: ColourSupport) -> Vec<Vec<Span>> {
+    // one tile, a line per row of it. One-row tiles are just what goes between the lines either side (" C ")
+    let colour = match face {
+        Face::Half(Some(c)) | Face::Scored(c) => Some(c),
+        _ => None,
+    };
+    let plain = |text: String| vec![Span::plain(text)];
+    match (style, large) {
+        (BoardStyle::Ascii, _) | (BoardStyle::Tiles, false) if matches!(face, Face::Flagged) => vec![vec![Span::styled(format!(" {letter} "), Style::reversed())]],
+        (BoardStyle::Ascii, _) => match (face, colour) {
+            (Face::Edge, _) => vec![plain(String::from(" - "))],
+            (_, Some(c)) => vec![vec![Span::plain(" "), theme.paint(&letter.to_string(), c, support), Span::plain(" ")]],
+            (_, None) => vec![plain(format!(" {letter} "))],
+        },
+        (BoardStyle::Tiles, false) => match (face, colour) {
+            (Face::Edge, _) => vec![plain(String::from("───"))],
+            (_, Some(c)) => vec![vec![theme.fill(&format!(" {letter} "), c, support)]],
+            (_, None) => vec![plain(format!(" {letter} "))],
+        },
+        (BoardStyle::Tiles, true) => {
+            // a scored tile is solid colour with no border, like the real game's
+            let blank = plain(String::from("     "));
+            match face {
+                Face::Plain => vec![plain(String::from("╭───╮")), plain(format!("│ {letter} │")), plain(String::from("╰───╯"))],
+                Face::Flagged => vec![plain(String::from("╭───╮")), vec![Span::plain("│"), Span::styled(format!(" {letter} "), Style::reversed()), Span::plain("│")], plain(String::from("╰───╯"))],
+                Face::Half(None) => vec![blank.clone(), plain(format!("│ {letter} │")), blank],
+                Face::Edge => vec![blank.clone(), plain(String::from("╶───╴")), blank],
+                Face::Half(Some(c)) => vec![blank.clone(), vec![theme.fill(&format!("  {letter}  "), c, support)], blank],
+                Face::Scored(c) => ["     ".to_string(), format!("  {letter}  "), "     ".to_string()].iter().map(|part| vec![theme.fill(part, c, support)]).collect(),
+            }
+        },
+    }
+}
+
+pub(crate) fn row(style: BoardStyle, large: bool, letters: &str, faces: &[Face], theme: &Theme, support: ColourSupport) -> Vec<Vec<Span>> {
+    // one row of the board as it gets printed, a line per row of the tiles (so three for large ones)
+    // letters can be short (or empty) for blank tiles
+    let letters = letters.chars().chain(std::iter::repeat(' '));
+    let tiles: Vec<Vec<Vec<Span>>> = letters.zip(faces).map(|(letter, face)| tile(style, large, letter, *face, theme, support)).collect();
+    let (left, between, right) = match (style, large) {
+        (BoardStyle::Ascii, _) => ("|", "|", "|"),
+        (BoardStyle::Tiles, false) => ("│", "│", "│"),
+        (BoardStyle::Tiles, true) => ("", " ", ""),
+    };
+    let mut lines = Vec::new();
+    for line in 0..tiles[0].len() {
+        let mut spans = vec![Span::plain(left)];
+        for (index, tile) in tiles.iter().enumerate() {
+            if index > 0 {
+                spans.push(Span::plain(between));
+            }
+            spans.extend(tile[line].iter().cloned());
+        }
+        spans.push(Span::plain(right));
+        lines.push(spans);
+    }
+    lines
+}
+
+pub fn edge(style: BoardStyle, edge: Edge) -> String {
+    match style {
+        BoardStyle::Ascii => "-".repeat(WORD_LENGTH * 4 + 1),
+        BoardStyle::Tiles => {
+            let (left, middle, right) = match edge {
+                Edge::Top => ('┌', '┬', '┐'),
+                Edge::Between => ('├', '┼', '┤'),
+                Edge::Bottom => ('└', '┴', '┘'),
+            };
+            format!("{left}{}{right}", ["───"; WORD_LENGTH].join(&middle.to_string()))
+        },
+    }
+}
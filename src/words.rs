@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::daily;
+
+// the word lists: what's allowed as a guess, and what the secret word gets picked from
+// the built-in ones are words/guesses.txt and words/secrets.txt, made into tables at compile time by build.rs
+// (which also checks them), so there's nothing to load at start up and checking a guess is a hash table lookup.
+// The config file can swap in lists of its own (see config.rs), which are read in when the game starts
+
+include!(concat!(env!("OUT_DIR"), "/words.rs")); // GUESS_BITS, GUESS_TABLE and SECRETS
+include!("words/pack.rs"); // pack, hash and unpack, the same ones build.rs made the tables with
+
+pub enum Guesses {
+    Builtin,
+    Custom(HashSet<String>), // uppercase
+}
+
+impl Guesses {
+    pub fn contains(&self, word: &str) -> bool { // any case
+        match self {
+            Guesses::Builtin => {
+                let Some(packed) = pack(&word.to_lowercase()) else {
+                    return false; // not a-z, so can't be in there
+                };
+                let mut slot = hash(packed, GUESS_BITS);
+                loop { // along from where it should be until it's found, or a gap shows it isn't there
+                    match GUESS_TABLE[slot] {
+                        0 => return false,
+                        found if found == packed => return true,
+                        _ => slot = (slot + 1) % GUESS_TABLE.len(),
+                    }
+                }
+            },
+            Guesses::Custom(words) => words.contains(&word.to_uppercase()),
+        }
+    }
+}
+
+pub enum Secrets {
+    Builtin,
+    Custom(Vec<String>),
+}
+
+impl Secrets {
+    pub fn len(&self) -> usize {
+        match self {
+            Secrets::Builtin => SECRETS.len(),
+            Secrets::Custom(words) => words.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> String { // uppercase, like a Word
+        match self {
+            Secrets::Builtin => unpack(SECRETS[index]),
+            Secrets::Custom(words) => words[index].to_uppercase(),
+        }
+    }
+
+    pub fn choose_secret(&self) -> String {
+        let rand_position = rand::thread_rng().gen_range(0..self.len());
+        self.get(rand_position)
+    }
+
+    pub fn choose_daily(&self, day: i64) -> String {
+        // everyone gets the same word on the same day
+        self.get(daily::puzzle_index(day, self.len()))
+    }
+}
@@ -0,0 +1,17 @@
+// how a word is stored in the tables: shared by build.rs, which makes them, and src/words.rs, which reads them
+// (both include! this file, so the two can't disagree)
+
+fn pack(word: &str) -> Option<u32> { // five bits a letter, a = 1
+    if word.len() != 5 || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return None;
+    }
+    Some(word.bytes().fold(0, |packed, b| packed << 5 | (b - b'a' + 1) as u32))
+}
+
+fn hash(packed: u32, bits: u32) -> usize {
+    (packed.wrapping_mul(0x9E37_79B1) >> (32 - bits)) as usize
+}
+
+fn unpack(packed: u32) -> String { // uppercase, like a Word
+    (0..5).rev().map(|i| (b'A' + ((packed >> (i * 5)) & 31) as u8 - 1) as char).collect()
+}